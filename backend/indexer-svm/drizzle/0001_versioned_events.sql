@@ -0,0 +1,7 @@
+ALTER TABLE "indexer_solana"."receiptsClaimed" ADD COLUMN "schema_version" integer;--> statement-breakpoint
+ALTER TABLE "indexer_solana"."receiptsClaimed" ADD COLUMN "receipt_hash" text;--> statement-breakpoint
+ALTER TABLE "indexer_solana"."receiptsClaimed" ADD COLUMN "source_tx_hash" text;--> statement-breakpoint
+ALTER TABLE "indexer_solana"."receiptsSent" ADD COLUMN "schema_version" integer;--> statement-breakpoint
+ALTER TABLE "indexer_solana"."receiptsSent" ADD COLUMN "fee_amount" numeric(78, 0);--> statement-breakpoint
+ALTER TABLE "indexer_solana"."receiptsSent" ADD COLUMN "fee_token" text;--> statement-breakpoint
+ALTER TABLE "indexer_solana"."receiptsSent" ADD COLUMN "quote_timestamp" numeric(78, 0);
\ No newline at end of file
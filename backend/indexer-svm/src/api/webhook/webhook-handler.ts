@@ -1,5 +1,5 @@
 import { clusterApiUrl, Connection, PublicKey } from "@solana/web3.js";
-import { BorshCoder, EventParser, Program, Idl } from "@coral-xyz/anchor";
+import { BorshCoder, EventParser, Program, Idl, utils } from "@coral-xyz/anchor";
 import idl from "../../idl/idl";
 import { receiptsClaimed, receiptsMeta, receiptsSent } from "../../db/schema";
 import { SolanaTransaction } from "./types";
@@ -31,17 +31,20 @@ export async function webhookHandler(request, reply) {
     if (!Array.isArray(events)) return "pong";
 
     for (const event of events) {
-      const logs = eventParser.parseLogs(event.meta.logMessages);
+      const logs = [...eventParser.parseLogs(event.meta.logMessages), ...parseCpiEvents(event)];
 
       for (const log of logs) {
         let insertValues: any = null;
         let model: any = null;
 
         switch (log.name) {
+          // `SendEvent` and `ReceivePayload` are emitted by bridge versions before the `*V2` events
           case "SendEvent":
+          case "SendEventV2":
             ({ model, values: insertValues } = processSendEvent(log, event));
             break;
           case "ReceivePayload":
+          case "ReceiveEventV2":
             ({ model, values: insertValues } = await processReceiveEvent(log, event));
             break;
           default:
@@ -79,6 +82,26 @@ export async function webhookHandler(request, reply) {
   return "pong";
 }
 
+// Anchor's tag of self-CPI event instructions, followed by the event discriminator and data
+const EVENT_IX_TAG_LE = Buffer.from([0xe4, 0x45, 0xa5, 0x2e, 0x51, 0xcb, 0x9a, 0x1d]);
+
+// Bridges built with the `event-cpi` feature emit events as self-CPIs instead of logs
+function parseCpiEvents(event: SolanaTransaction) {
+  const coder = new BorshCoder(idl as Idl);
+  const accountKeys = [
+    ...event.transaction.message.accountKeys,
+    ...event.meta.loadedAddresses.writable,
+    ...event.meta.loadedAddresses.readonly
+  ];
+  return (event.meta.innerInstructions ?? [])
+    .flatMap(({ instructions }) => instructions)
+    .filter((ix) => accountKeys[ix.programIdIndex] === idl.address)
+    .map((ix) => Buffer.from(utils.bytes.bs58.decode(ix.data)))
+    .filter((data) => data.subarray(0, 8).equals(EVENT_IX_TAG_LE))
+    .map((data) => coder.events.decode(data.subarray(8).toString("base64")))
+    .filter((log) => log !== null);
+}
+
 function processSendEvent(log: any, event: SolanaTransaction) {
   return {
     model: receiptsSent,
@@ -95,7 +118,13 @@ function processSendEvent(log: any, event: SolanaTransaction) {
       chainTo: safeBigIntFromHex(log.data.chain_to),
       eventId: safeBigIntFromHex(log.data.event_id),
       flags: safeHexToNumber(log.data.flags),
-      data: safeHexToString(log.data.flag_data)
+      data: safeHexToString(log.data.flag_data),
+      ...(log.name === "SendEventV2" && {
+        schemaVersion: log.data.schema_version,
+        feeAmount: safeNumberToString(log.data.fee_amount),
+        feeToken: toHex(log.data.fee_token.toBase58()),
+        quoteTimestamp: safeNumberToString(log.data.quote_timestamp)
+      })
     }
   };
 }
@@ -103,7 +132,7 @@ function processSendEvent(log: any, event: SolanaTransaction) {
 /**
  * Extracts and formats data from a Solana receive event log for claimed receipt processing.
  *
- * This asynchronous function converts raw log data from a Solana transaction event into a structured object. It parses hexadecimal values such as token addresses, amounts, chain identifiers, event ID, and flags into appropriate formats, preparing the data for insertion into the database under the claimed receipts model.
+ * This asynchronous function converts raw log data from a Solana transaction event (`ReceivePayload` or `ReceiveEventV2`) into a structured object. It parses hexadecimal values such as token addresses, amounts, chain identifiers, event ID, and flags into appropriate formats, preparing the data for insertion into the database under the claimed receipts model.
  *
  * @param log - The log entry containing raw event data including recipient addresses, amounts, chain identifiers, and flag details.
  * @param event - The Solana transaction event providing metadata such as the block time.
@@ -125,7 +154,12 @@ async function processReceiveEvent(log: any, event: SolanaTransaction) {
       chainFrom: safeBigIntFromHex(log.data.chain_from),
       eventId: safeBigIntFromHex(log.data.event_id),
       flags: safeHexToNumber(log.data.flags),
-      data: safeHexToString(log.data.flag_data)
+      data: safeHexToString(log.data.flag_data),
+      ...(log.name === "ReceiveEventV2" && {
+        schemaVersion: log.data.schema_version,
+        receiptHash: toHexFromBytes(log.data.receipt_hash),
+        sourceTxHash: toHexFromBytes(log.data.source_tx_hash)
+      })
     }
   };
 }
@@ -32,7 +32,11 @@ export const receiptsClaimed = indexerSolana.table(
     chainFrom: numeric("chain_from", { precision: 78, scale: 0 }).notNull(),
     eventId: numeric("event_id", { precision: 78, scale: 0 }).notNull(),
     flags: numeric({ precision: 78, scale: 0 }).notNull(),
-    data: text().notNull()
+    data: text().notNull(),
+    // from `ReceiveEventV2`, null for earlier events
+    schemaVersion: integer("schema_version"),
+    receiptHash: text("receipt_hash"),
+    sourceTxHash: text("source_tx_hash")
   },
   (table) => [
     primaryKey({
@@ -57,7 +61,12 @@ export const receiptsSent = indexerSolana.table(
     chainTo: numeric("chain_to", { precision: 78, scale: 0 }).notNull(),
     eventId: numeric("event_id", { precision: 78, scale: 0 }).notNull(),
     flags: numeric({ precision: 78, scale: 0 }).notNull(),
-    data: text().notNull()
+    data: text().notNull(),
+    // from `SendEventV2`, null for earlier events
+    schemaVersion: integer("schema_version"),
+    feeAmount: numeric("fee_amount", { precision: 78, scale: 0 }),
+    feeToken: text("fee_token"),
+    quoteTimestamp: numeric("quote_timestamp", { precision: 78, scale: 0 })
   },
   (table) => [
     primaryKey({
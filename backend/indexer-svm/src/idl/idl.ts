@@ -949,6 +949,32 @@ export const bridgeIdl = {
         3,
         30
       ]
+    },
+    {
+      "name": "ReceiveEventV2",
+      "discriminator": [
+        25,
+        133,
+        232,
+        75,
+        58,
+        188,
+        121,
+        216
+      ]
+    },
+    {
+      "name": "SendEventV2",
+      "discriminator": [
+        96,
+        5,
+        227,
+        108,
+        87,
+        70,
+        188,
+        169
+      ]
     }
   ],
   "errors": [
@@ -1064,6 +1090,85 @@ export const bridgeIdl = {
         ]
       }
     },
+    {
+      "name": "ReceiveEventV2",
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "schema_version",
+            "type": "u8"
+          },
+          {
+            "name": "to",
+            "type": "pubkey"
+          },
+          {
+            "name": "token_address_to",
+            "type": "pubkey"
+          },
+          {
+            "name": "amount_to",
+            "type": "u64"
+          },
+          {
+            "name": "chain_from",
+            "type": "u64"
+          },
+          {
+            "name": "chain_to",
+            "type": "u64"
+          },
+          {
+            "name": "event_id",
+            "type": "u64"
+          },
+          {
+            "name": "expires_at",
+            "type": "u64"
+          },
+          {
+            "name": "signer_epoch",
+            "type": "u64"
+          },
+          {
+            "name": "flags",
+            "type": {
+              "array": [
+                "u8",
+                32
+              ]
+            }
+          },
+          {
+            "name": "flag_data",
+            "type": "bytes"
+          },
+          {
+            "name": "receipt_hash",
+            "type": {
+              "array": [
+                "u8",
+                32
+              ]
+            }
+          },
+          {
+            "name": "source_tx_hash",
+            "type": {
+              "array": [
+                "u8",
+                32
+              ]
+            }
+          },
+          {
+            "name": "slot",
+            "type": "u64"
+          }
+        ]
+      }
+    },
     {
       "name": "SendEvent",
       "type": {
@@ -1136,6 +1241,98 @@ export const bridgeIdl = {
         ]
       }
     },
+    {
+      "name": "SendEventV2",
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "schema_version",
+            "type": "u8"
+          },
+          {
+            "name": "from",
+            "type": "pubkey"
+          },
+          {
+            "name": "to",
+            "type": {
+              "array": [
+                "u8",
+                20
+              ]
+            }
+          },
+          {
+            "name": "token_address_from",
+            "type": "pubkey"
+          },
+          {
+            "name": "token_address_to",
+            "type": {
+              "array": [
+                "u8",
+                20
+              ]
+            }
+          },
+          {
+            "name": "amount_from",
+            "type": "u64"
+          },
+          {
+            "name": "amount_to",
+            "type": {
+              "array": [
+                "u8",
+                32
+              ]
+            }
+          },
+          {
+            "name": "chain_from",
+            "type": "u64"
+          },
+          {
+            "name": "chain_to",
+            "type": "u64"
+          },
+          {
+            "name": "event_id",
+            "type": "u64"
+          },
+          {
+            "name": "flags",
+            "type": {
+              "array": [
+                "u8",
+                32
+              ]
+            }
+          },
+          {
+            "name": "flag_data",
+            "type": "bytes"
+          },
+          {
+            "name": "fee_amount",
+            "type": "u64"
+          },
+          {
+            "name": "fee_token",
+            "type": "pubkey"
+          },
+          {
+            "name": "quote_timestamp",
+            "type": "u64"
+          },
+          {
+            "name": "slot",
+            "type": "u64"
+          }
+        ]
+      }
+    },
     {
       "name": "TokenConfig",
       "type": {
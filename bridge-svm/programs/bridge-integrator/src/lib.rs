@@ -0,0 +1,88 @@
+use anchor_lang::prelude::*;
+use bridge_svm::cpi::accounts::Send as BridgeSend;
+use bridge_svm::program::AmbSolBridge;
+
+declare_id!("4ttPetG7nxUVoALgqGyQVJjUdKSZCyc3sin7PJP6awqb");
+
+// Example integrator: bridges the user's tokens by calling `send` through CPI.
+// The signed quote's Ed25519 instruction can be anywhere in the transaction.
+#[program]
+pub mod bridge_integrator {
+    use super::*;
+
+    pub fn bridge_tokens(
+        ctx: Context<BridgeTokens>,
+        serialized_args: Vec<u8>,
+        quote_id: u64,
+        max_fee: u64,
+        deadline: u64,
+    ) -> Result<()> {
+        // the integrator's own logic would go here
+
+        let accounts = BridgeSend {
+            state: ctx.accounts.state.to_account_info(),
+            sender: ctx.accounts.sender.to_account_info(),
+            sender_token_account: ctx.accounts.sender_token_account.to_account_info(),
+            bridge_token: ctx.accounts.bridge_token.to_account_info(),
+            bridge_token_account: ctx.accounts.bridge_token_account.as_ref().map(|account| account.to_account_info()),
+            mint: ctx.accounts.mint.to_account_info(),
+            consumed_quote: ctx.accounts.consumed_quote.to_account_info(),
+            send_accumulator: Some(ctx.accounts.send_accumulator.to_account_info()),
+            receipt: None,
+            ix_sysvar: ctx.accounts.ix_sysvar.to_account_info(),
+            token_program: ctx.accounts.token_program.to_account_info(),
+            system_program: ctx.accounts.system_program.to_account_info(),
+            #[cfg(feature = "event-cpi")]
+            event_authority: ctx.accounts.event_authority.to_account_info(),
+            #[cfg(feature = "event-cpi")]
+            program: ctx.accounts.bridge_program.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new(ctx.accounts.bridge_program.to_account_info(), accounts);
+        bridge_svm::cpi::send(cpi_ctx, serialized_args, quote_id, max_fee, deadline)
+    }
+}
+
+// Same accounts as the bridge `Send`; the bridge checks them.
+#[derive(Accounts)]
+pub struct BridgeTokens<'info> {
+    /// CHECK: checked by the bridge.
+    #[account(mut)]
+    pub state: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub sender: Signer<'info>,
+
+    /// CHECK: checked by the bridge.
+    #[account(mut)]
+    pub sender_token_account: UncheckedAccount<'info>,
+
+    /// CHECK: checked by the bridge.
+    pub bridge_token: UncheckedAccount<'info>,
+
+    /// CHECK: checked by the bridge.
+    #[account(mut)]
+    pub bridge_token_account: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: checked by the bridge.
+    #[account(mut)]
+    pub mint: UncheckedAccount<'info>,
+
+    /// CHECK: created by the bridge.
+    #[account(mut)]
+    pub consumed_quote: UncheckedAccount<'info>,
+
+    /// CHECK: checked by the bridge.
+    #[account(mut)]
+    pub send_accumulator: UncheckedAccount<'info>,
+
+    /// CHECK: checked by the bridge.
+    pub ix_sysvar: UncheckedAccount<'info>,
+    /// CHECK: checked by the bridge.
+    pub token_program: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+    pub bridge_program: Program<'info, AmbSolBridge>,
+
+    /// CHECK: the bridge's event authority, checked by the bridge.
+    #[cfg(feature = "event-cpi")]
+    pub event_authority: UncheckedAccount<'info>,
+}
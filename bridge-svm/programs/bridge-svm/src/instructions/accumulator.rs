@@ -0,0 +1,73 @@
+use crate::structs::*;
+use crate::utils::merkle::{append_leaf, empty_root, TREE_DEPTH};
+use crate::utils::events::emit_event;
+use crate::utils::receipt::{message_leaf_hash, send_receipt_hash};
+use anchor_lang::prelude::*;
+
+// On existing deployments sends go without the accumulator until it's created, and need it from then on.
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+pub struct InitSendAccumulator<'info> {
+    #[account(
+        mut,
+        has_one = admin @ CustomError::NotAdmin,
+        constraint = state.version == GlobalState::VERSION @ CustomError::UnsupportedVersion,
+        seeds = [GlobalState::SEED_PREFIX], bump
+    )]
+    pub state: Account<'info, GlobalState>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = SendAccumulator::ACCOUNT_SIZE,
+        seeds = [SendAccumulator::SEED_PREFIX], bump
+    )]
+    pub send_accumulator: Box<Account<'info, SendAccumulator>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn init_send_accumulator(ctx: Context<InitSendAccumulator>) -> Result<()> {
+    ctx.accounts.send_accumulator.set_inner(SendAccumulator {
+        first_event_id: ctx.accounts.state.nonce + 1,
+        count: 0,
+        branch: [[0; 32]; TREE_DEPTH],
+        bump: ctx.bumps.send_accumulator,
+    });
+    ctx.accounts.state.send_root = empty_root();
+
+    emit_event!(ctx, SendAccumulatorInitialized {
+        sender: ctx.accounts.admin.key(),
+        first_event_id: ctx.accounts.send_accumulator.first_event_id,
+        send_root: ctx.accounts.state.send_root,
+    });
+    Ok(())
+}
+
+// Appends a send to the tree, called right after its event id is taken from `state.nonce`.
+pub fn accumulate_send(accumulator: Option<&mut Box<Account<SendAccumulator>>>, state: &mut GlobalState, event: &SendEvent) -> Result<()> {
+    accumulate(accumulator, state, event.event_id, send_receipt_hash(event))
+}
+
+// Same as `accumulate_send`; messages share the event id sequence, so they must be leaves too.
+pub fn accumulate_message(accumulator: Option<&mut Box<Account<SendAccumulator>>>, state: &mut GlobalState, event: &MessageSent) -> Result<()> {
+    accumulate(accumulator, state, event.event_id, message_leaf_hash(event))
+}
+
+fn accumulate(accumulator: Option<&mut Box<Account<SendAccumulator>>>, state: &mut GlobalState, event_id: u64, leaf: [u8; 32]) -> Result<()> {
+    // `send_root` is zero only before the accumulator is created, after that every event id must be a leaf
+    let Some(accumulator) = accumulator else {
+        require!(state.send_root == [0; 32], CustomError::AccumulatorRequired);
+        return Ok(());
+    };
+    require!(accumulator.count < 1 << TREE_DEPTH, CustomError::AccumulatorFull);
+    require!(event_id == accumulator.first_event_id + accumulator.count, CustomError::InvalidNonce);
+
+    let index = accumulator.count;
+    state.send_root = append_leaf(&mut accumulator.branch, index, leaf);
+    accumulator.count += 1;
+    Ok(())
+}
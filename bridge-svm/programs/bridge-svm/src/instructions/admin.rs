@@ -1,9 +1,13 @@
+use crate::instructions::{check_timelock_delay, has_role};
 use crate::structs::*;
+use crate::utils::events::emit_event;
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::bpf_loader_upgradeable;
 use anchor_spl::associated_token::AssociatedToken;
 use anchor_spl::token::Token;
 use anchor_spl::token_interface::{Mint, TokenAccount};
 
+#[cfg_attr(feature = "event-cpi", event_cpi)]
 #[derive(Accounts)]
 pub struct Initialize<'info> {
     #[account(
@@ -14,26 +18,42 @@ pub struct Initialize<'info> {
     )]
     pub state: Account<'info, GlobalState>,
 
+    // Only the upgrade authority can initialize, so a fresh deployment can't be front-run.
     #[account(mut)]
     pub admin: Signer<'info>,
 
+    #[account(
+        constraint = program_data.upgrade_authority_address == Some(admin.key()) @ CustomError::NotUpgradeAuthority,
+        seeds = [crate::ID.as_ref()], bump,
+        seeds::program = bpf_loader_upgradeable::ID,
+    )]
+    pub program_data: Account<'info, ProgramData>,
+
     pub system_program: Program<'info, System>,
 }
 
+#[cfg_attr(feature = "event-cpi", event_cpi)]
 #[derive(Accounts)]
 pub struct CreateToken<'info> {
     #[account(
-        has_one = admin @ CustomError::NotAdmin,
+        constraint = has_role(&state, authority.key, authority_roles.as_deref(), Role::TokenManager) @ CustomError::MissingRole,
+        constraint = !state.is_paused(PAUSE_TOKEN_REGISTRATION) @ CustomError::Paused,
+        constraint = state.version == GlobalState::VERSION @ CustomError::UnsupportedVersion,
         seeds = [GlobalState::SEED_PREFIX], bump
     )]
     pub state: Account<'info, GlobalState>,
 
     #[account(mut)]
-    pub admin: Signer<'info>,
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [RoleAccount::SEED_PREFIX, authority.key().as_ref()], bump
+    )]
+    pub authority_roles: Option<Account<'info, RoleAccount>>,
 
     #[account(
         init_if_needed,
-        payer = admin,
+        payer = authority,
         space = TokenConfig::ACCOUNT_SIZE,
         seeds = [TokenConfig::SEED_PREFIX, mint.key().as_ref()], bump
     )]
@@ -41,7 +61,7 @@ pub struct CreateToken<'info> {
 
     #[account(
         init_if_needed,
-        payer = admin,
+        payer = authority,
         associated_token::mint = mint,
         associated_token::authority = bridge_token,
     )]
@@ -57,30 +77,114 @@ pub struct CreateToken<'info> {
     pub system_program: Program<'info, System>,
 }
 
+#[cfg_attr(feature = "event-cpi", event_cpi)]
 #[derive(Accounts)]
 pub struct UpdateState<'info> {
     #[account(
         mut,
-        has_one = admin @ CustomError::NotAdmin,
+        constraint = state.version == GlobalState::VERSION @ CustomError::UnsupportedVersion,
         seeds = [GlobalState::SEED_PREFIX], bump
     )]
     pub state: Account<'info, GlobalState>,
 
     #[account(mut)]
-    pub admin: Signer<'info>,
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [RoleAccount::SEED_PREFIX, authority.key().as_ref()], bump
+    )]
+    pub authority_roles: Option<Account<'info, RoleAccount>>,
+}
+
+impl UpdateState<'_> {
+    fn require_admin(&self) -> Result<()> {
+        require!(self.authority.key() == self.state.admin, CustomError::NotAdmin);
+        Ok(())
+    }
+
+    fn require_role(&self, role: Role) -> Result<()> {
+        require!(
+            has_role(&self.state, self.authority.key, self.authority_roles.as_deref(), role),
+            CustomError::MissingRole
+        );
+        Ok(())
+    }
+}
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+pub struct UpdateToken<'info> {
+    #[account(
+        constraint = state.version == GlobalState::VERSION @ CustomError::UnsupportedVersion,
+        seeds = [GlobalState::SEED_PREFIX], bump
+    )]
+    pub state: Account<'info, GlobalState>,
+
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [RoleAccount::SEED_PREFIX, authority.key().as_ref()], bump
+    )]
+    pub authority_roles: Option<Account<'info, RoleAccount>>,
+
+    #[account(
+        mut,
+        constraint = bridge_token.version == TokenConfig::VERSION @ CustomError::UnsupportedVersion,
+        seeds = [TokenConfig::SEED_PREFIX, bridge_token.token.as_ref()], bump = bridge_token.bump
+    )]
+    pub bridge_token: Account<'info, TokenConfig>,
+}
+
+// Guardians can only pause, either the whole bridge or a single token (when `bridge_token` is passed).
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+pub struct EmergencyPause<'info> {
+    #[account(
+        mut,
+        constraint = state.is_guardian(guardian.key) @ CustomError::NotGuardian,
+        constraint = state.version == GlobalState::VERSION @ CustomError::UnsupportedVersion,
+        seeds = [GlobalState::SEED_PREFIX], bump
+    )]
+    pub state: Account<'info, GlobalState>,
+
+    pub guardian: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = bridge_token.version == TokenConfig::VERSION @ CustomError::UnsupportedVersion,
+        seeds = [TokenConfig::SEED_PREFIX, bridge_token.token.as_ref()], bump = bridge_token.bump
+    )]
+    pub bridge_token: Option<Account<'info, TokenConfig>>,
 }
 
 pub fn initialize(
     ctx: Context<Initialize>,
     send_signer: Pubkey,
     receive_signer: Pubkey,
+    timelock_delay: u64,
 ) -> Result<()> {
+    check_timelock_delay(timelock_delay)?;
     let state = &mut ctx.accounts.state;
+    state.version = GlobalState::VERSION;
     state.admin = ctx.accounts.admin.key();
     state.nonce = 0;
-    state.pause = false;
+    state.pause_flags = 0;
+    state.pause_reason = 0;
     state.send_signer = send_signer;
     state.receive_signer = receive_signer;
+    state.guardians = vec![];
+    state.timelock_delay = timelock_delay;
+    state.timelock_nonce = 0;
+    state.native_send_amount = 0;
+    state.signer_epoch = 0;
+    state.send_root = [0; 32];
+
+    emit_event!(ctx, StateInitialized {
+        admin: state.admin,
+        send_signer,
+        receive_signer,
+        timelock_delay,
+    });
     Ok(())
 }
 
@@ -91,9 +195,15 @@ pub fn initialize_token(
     is_mintable: bool,
 ) -> Result<()> {
     let bridge_token = &mut ctx.accounts.bridge_token;
+    // version is 0 only if the account was just created by `init_if_needed`
+    require!(
+        bridge_token.version == 0 || bridge_token.version == TokenConfig::VERSION,
+        CustomError::UnsupportedVersion
+    );
+    require!(!bridge_token.is_migrated(), CustomError::TokenMigrated);
 
     require!(
-        is_mintable == !ctx.accounts.bridge_token_account.is_some(),
+        is_mintable == ctx.accounts.bridge_token_account.is_none(),
         ErrorCode::RequireViolated
     );
     if is_mintable {
@@ -109,35 +219,104 @@ pub fn initialize_token(
         amb_decimals,
         is_mintable,
         ctx.bumps.bridge_token,
+        bridge_token.paused,  // re-initializing must not unpause the token
     ));
 
+    emit_event!(ctx, TokenInitialized {
+        sender: ctx.accounts.authority.key(),
+        token: ctx.accounts.mint.key(),
+        amb_token,
+        amb_decimals,
+        is_mintable,
+    });
     Ok(())
 }
 
-pub fn set_pause(ctx: Context<UpdateState>, pause: bool) -> Result<()> {
-    ctx.accounts.state.pause = pause;
+pub fn set_pause(ctx: Context<UpdateState>, flags: u8, pause: bool, reason: u16) -> Result<()> {
+    require!(flags & !PAUSE_ALL == 0, CustomError::InvalidArgs);
+
+    // pausers and guardians can only pause; unpausing is up to the admin
+    if pause {
+        ctx.accounts.require_role(Role::Pauser)?;
+    } else {
+        ctx.accounts.require_admin()?;
+    }
+
+    let state = &mut ctx.accounts.state;
+    if pause {
+        state.pause_flags |= flags;
+    } else {
+        state.pause_flags &= !flags;
+    }
+    state.pause_reason = reason;
+
+    emit_event!(ctx, PauseChanged {
+        sender: ctx.accounts.authority.key(),
+        pause_flags: state.pause_flags,
+        reason,
+    });
     Ok(())
 }
 
-pub fn set_signers(ctx: Context<UpdateState>, send_signer: Pubkey, receive_signer: Pubkey) -> Result<()> {
-    ctx.accounts.state.send_signer = send_signer;
-    ctx.accounts.state.receive_signer = receive_signer;
+pub fn set_token_pause(ctx: Context<UpdateToken>, pause: bool) -> Result<()> {
+    let allowed = if pause {
+        has_role(&ctx.accounts.state, ctx.accounts.authority.key, ctx.accounts.authority_roles.as_deref(), Role::Pauser)
+    } else {
+        ctx.accounts.authority.key() == ctx.accounts.state.admin
+    };
+    require!(allowed, CustomError::MissingRole);
+    // a migrated token stays paused for good
+    require!(pause || !ctx.accounts.bridge_token.is_migrated(), CustomError::TokenMigrated);
+    ctx.accounts.bridge_token.paused = pause;
+
+    emit_event!(ctx, TokenPauseChanged {
+        sender: ctx.accounts.authority.key(),
+        token: ctx.accounts.bridge_token.token,
+        paused: pause,
+    });
     Ok(())
 }
 
-pub fn withdraw(ctx: Context<UpdateState>, amount: u64) -> Result<()> {
-    let vault = &ctx.accounts.state;
-    let admin = &ctx.accounts.admin;
+pub fn emergency_pause(ctx: Context<EmergencyPause>, reason: u16) -> Result<()> {
+    let token = match &mut ctx.accounts.bridge_token {
+        Some(bridge_token) => {
+            bridge_token.paused = true;
+            Some(bridge_token.token)
+        }
+        None => {
+            ctx.accounts.state.pause_flags = PAUSE_ALL;
+            ctx.accounts.state.pause_reason = reason;
+            None
+        }
+    };
 
-    // Ensure the vault has enough balance
-    require!(
-        **vault.to_account_info().lamports.borrow() >= amount,
-        ErrorCode::RequireGteViolated
-    );
+    emit_event!(ctx, EmergencyPaused {
+        guardian: ctx.accounts.guardian.key(),
+        token,
+        reason,
+    });
+    Ok(())
+}
+
+pub fn set_guardians(ctx: Context<UpdateState>, guardians: Vec<Pubkey>) -> Result<()> {
+    ctx.accounts.require_admin()?;
+    require!(guardians.len() <= MAX_GUARDIANS, CustomError::InvalidArgs);
+    ctx.accounts.state.guardians = guardians.clone();
+
+    emit_event!(ctx, GuardiansChanged {
+        sender: ctx.accounts.authority.key(),
+        guardians,
+    });
+    Ok(())
+}
 
-    // Transfer SOL from vault to user
-    **vault.to_account_info().try_borrow_mut_lamports()? -= amount;
-    **admin.to_account_info().try_borrow_mut_lamports()? += amount;
+pub fn set_native_send_amount(ctx: Context<UpdateState>, amount: u64) -> Result<()> {
+    ctx.accounts.require_admin()?;
+    ctx.accounts.state.native_send_amount = amount;
 
+    emit_event!(ctx, NativeSendAmountChanged {
+        sender: ctx.accounts.authority.key(),
+        amount,
+    });
     Ok(())
 }
@@ -0,0 +1,249 @@
+use crate::instructions::{check_receive_expiry, give_tokens_to, send_native_to_receiver, receive_event, use_receiver_nonce, verify_receive_signature};
+use crate::structs::*;
+use crate::utils::events::emit_event;
+use crate::utils::flags::{BridgeFlags, ReceiveFlagData, TAG_CALL, TAG_TO_TOKEN_ACCOUNT};
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{
+    instruction::{AccountMeta, Instruction},
+    program::invoke_signed,
+    sysvar::instructions::ID as SYSVAR_INSTRUCTIONS_ID,
+};
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token::Token;
+use anchor_spl::token_interface::{self, Mint, TokenAccount};
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+#[instruction(program_id: Pubkey)]
+pub struct SetCallTarget<'info> {
+    #[account(
+        has_one = admin @ CustomError::NotAdmin,
+        constraint = state.version == GlobalState::VERSION @ CustomError::UnsupportedVersion,
+        seeds = [GlobalState::SEED_PREFIX], bump
+    )]
+    pub state: Account<'info, GlobalState>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = CallTarget::ACCOUNT_SIZE,
+        seeds = [CallTarget::SEED_PREFIX, program_id.as_ref()], bump
+    )]
+    pub call_target: Account<'info, CallTarget>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// Same as `Receive`, but the tokens go through an escrow owned by the receiver's `call_authority` into the call target.
+// Remaining accounts are passed to the target instruction; `call_authority` among them is marked as signer.
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+pub struct ReceiveAndCall<'info> {
+    #[account(
+        mut,
+        constraint = !state.is_paused(PAUSE_RECEIVE) @ CustomError::Paused,
+        constraint = state.version == GlobalState::VERSION @ CustomError::UnsupportedVersion,
+        seeds = [GlobalState::SEED_PREFIX], bump
+    )]
+    pub state: Box<Account<'info, GlobalState>>,
+
+    #[account(mut)]
+    pub receiver: Signer<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = receiver,
+        associated_token::mint = mint,
+        associated_token::authority = receiver,
+        associated_token::token_program = token_program,
+    )]
+    pub receiver_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = receiver,
+        space = NonceAccount::ACCOUNT_SIZE,
+        seeds = [NonceAccount::SEED_PREFIX, receiver.key().as_ref()], bump,
+    )]
+    pub receiver_nonce_account: Box<Account<'info, NonceAccount>>,
+
+    #[account(
+        constraint = !bridge_token.is_migrated() @ CustomError::TokenMigrated,
+        constraint = !bridge_token.paused @ CustomError::TokenPaused,
+        constraint = bridge_token.version == TokenConfig::VERSION @ CustomError::UnsupportedVersion,
+        seeds = [TokenConfig::SEED_PREFIX, mint.key().as_ref()], bump = bridge_token.bump
+    )]
+    pub bridge_token: Box<Account<'info, TokenConfig>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = bridge_token,
+    )]
+    pub bridge_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    #[account(mut)]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+
+    // Not whitelisted (or missing) target means plain delivery to the receiver.
+    #[account(
+        seeds = [CallTarget::SEED_PREFIX, target_program.key().as_ref()], bump = call_target.bump
+    )]
+    pub call_target: Option<Account<'info, CallTarget>>,
+
+    /// CHECK: checked against the signed `CallData`.
+    pub target_program: UncheckedAccount<'info>,
+
+    /// CHECK: PDA, only signs.
+    #[account(seeds = [CALL_AUTHORITY_SEED, receiver.key().as_ref()], bump)]
+    pub call_authority: UncheckedAccount<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = receiver,
+        associated_token::mint = mint,
+        associated_token::authority = call_authority,
+        associated_token::token_program = token_program,
+    )]
+    pub call_escrow: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// CHECK: The address check is needed because otherwise
+    /// the supplied Sysvar could be anything else.
+    #[account(address = SYSVAR_INSTRUCTIONS_ID)]
+    pub ix_sysvar: AccountInfo<'info>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+pub fn set_call_target(ctx: Context<SetCallTarget>, program_id: Pubkey, allowed: bool) -> Result<()> {
+    require!(program_id != crate::ID, CustomError::InvalidArgs);
+    ctx.accounts.call_target.set_inner(CallTarget {
+        program_id,
+        allowed,
+        bump: ctx.bumps.call_target,
+    });
+
+    emit_event!(ctx, CallTargetChanged { program_id, allowed });
+    Ok(())
+}
+
+// A failing target instruction fails the whole transaction, Solana can't catch a failed CPI.
+// In that case the receiver can submit again with `skip_call` to get the tokens delivered directly.
+pub fn receive_and_call<'info>(
+    ctx: Context<'_, '_, 'info, 'info, ReceiveAndCall<'info>>,
+    amount_to: u64,
+    event_id: u64,
+    expires_at: u64,
+    source: SourceRef,
+    flags: [u8; 32],
+    flag_data: Vec<u8>,
+    skip_call: bool,
+) -> Result<()> {
+    let args = ReceivePayload {
+        to: ctx.accounts.receiver.key(),
+        token_address_to: ctx.accounts.mint.key(),
+        amount_to,
+        chain_from: AMB_CHAIN_ID,
+        chain_to: SOLANA_CHAIN_ID,
+        event_id,
+        expires_at,
+        signer_epoch: ctx.accounts.state.signer_epoch,
+        source,
+        flags,
+        flag_data,
+    };
+    let serialized_args = args.signed_message()?;
+    verify_receive_signature(&ctx.accounts.ix_sysvar, ctx.accounts.state.receive_signer, &serialized_args)?;
+    check_receive_expiry(args.expires_at)?;
+
+    let flags = BridgeFlags::from_bytes(&args.flags).for_receive()?;
+    let flag_data = ReceiveFlagData::parse(&args.flag_data)?;
+    require!(!flag_data.data.has(TAG_TO_TOKEN_ACCOUNT), CustomError::InvalidFlagData);
+    use_receiver_nonce(&mut ctx.accounts.receiver_nonce_account, flag_data.nonce)?;
+    if flags.send_native_to_receiver {
+        send_native_to_receiver(&ctx.accounts.state, &ctx.accounts.receiver.to_account_info())?;
+    }
+
+    let call = flag_data.data.get(TAG_CALL).ok_or(CustomError::InvalidFlagData)?;
+    let call = CallData::try_from_slice(call).map_err(|_| CustomError::InvalidFlagData)?;
+    require!(ctx.accounts.target_program.key() == call.program_id, CustomError::InvalidArgs);
+
+    let whitelisted = ctx.accounts.call_target.as_ref().is_some_and(|target| target.allowed);
+    if skip_call || !whitelisted {
+        give_tokens_to(
+            &ctx.accounts.bridge_token,
+            ctx.accounts.bridge_token_account.as_ref().map(|account| account.to_account_info()),
+            ctx.accounts.receiver_token_account.to_account_info(),
+            &ctx.accounts.mint,
+            args.amount_to,
+            ctx.accounts.token_program.to_account_info(),
+        )?;
+
+        emit_event!(ctx, CallSkipped {
+            event_id,
+            program_id: call.program_id,
+        });
+        emit_event!(ctx, receive_event(args)?);
+        return Ok(());
+    }
+
+    // the escrow is shared by all targets of the receiver, anything left in it could be taken by the next call
+    require!(ctx.accounts.call_escrow.amount == 0, CustomError::EscrowNotEmpty);
+    give_tokens_to(
+        &ctx.accounts.bridge_token,
+        ctx.accounts.bridge_token_account.as_ref().map(|account| account.to_account_info()),
+        ctx.accounts.call_escrow.to_account_info(),
+        &ctx.accounts.mint,
+        args.amount_to,
+        ctx.accounts.token_program.to_account_info(),
+    )?;
+
+    let call_authority = ctx.accounts.call_authority.key();
+    let mut data = call.data;
+    data.extend_from_slice(&args.amount_to.to_le_bytes());
+    let ix = Instruction {
+        program_id: call.program_id,
+        accounts: ctx
+            .remaining_accounts
+            .iter()
+            .map(|account| AccountMeta {
+                pubkey: account.key(),
+                is_signer: account.is_signer || account.key() == call_authority,
+                is_writable: account.is_writable,
+            })
+            .collect(),
+        data,
+    };
+    let mut account_infos = ctx.remaining_accounts.to_vec();
+    account_infos.push(ctx.accounts.target_program.to_account_info());
+    let receiver = ctx.accounts.receiver.key();
+    let signer_seeds: &[&[&[u8]]] = &[&[CALL_AUTHORITY_SEED, receiver.as_ref(), &[ctx.bumps.call_authority]]];
+    invoke_signed(&ix, &account_infos, signer_seeds)?;
+
+    // whatever the target didn't take goes to the receiver, leaving the escrow empty
+    ctx.accounts.call_escrow.reload()?;
+    let refunded = ctx.accounts.call_escrow.amount;
+    if refunded > 0 {
+        let cpi_accounts = token_interface::TransferChecked {
+            from: ctx.accounts.call_escrow.to_account_info(),
+            mint: ctx.accounts.mint.to_account_info(),
+            to: ctx.accounts.receiver_token_account.to_account_info(),
+            authority: ctx.accounts.call_authority.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), cpi_accounts, signer_seeds);
+        token_interface::transfer_checked(cpi_ctx, refunded, ctx.accounts.mint.decimals)?;
+    }
+
+    emit_event!(ctx, CallExecuted {
+        event_id,
+        program_id: call.program_id,
+        amount: args.amount_to,
+        refunded,
+    });
+    emit_event!(ctx, receive_event(args)?);
+    Ok(())
+}
@@ -0,0 +1,164 @@
+use crate::instructions::{accumulate_message, check_quote_terms, check_receive_expiry, verify_receive_signature, verify_send_signature};
+use crate::structs::*;
+use crate::utils::events::emit_event;
+use crate::utils::transfer::transfer_native_from_user;
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{keccak::hash, sysvar::instructions::ID as SYSVAR_INSTRUCTIONS_ID};
+
+// No `SendReceipt` for messages, see its docs.
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+#[instruction(serialized_args: Vec<u8>, quote_id: u64)]
+pub struct SendMessage<'info> {
+    #[account(
+        mut,
+        constraint = !state.is_paused(PAUSE_SEND) @ CustomError::Paused,
+        constraint = state.version == GlobalState::VERSION @ CustomError::UnsupportedVersion,
+        seeds = [GlobalState::SEED_PREFIX], bump
+    )]
+    pub state: Account<'info, GlobalState>,
+
+    #[account(mut)]
+    pub sender: Signer<'info>,
+
+    // `init` fails if the quote was already used
+    #[account(
+        init,
+        payer = sender,
+        space = ConsumedQuote::ACCOUNT_SIZE,
+        seeds = [ConsumedQuote::SEED_PREFIX, sender.key().as_ref(), quote_id.to_le_bytes().as_ref()], bump
+    )]
+    pub consumed_quote: Account<'info, ConsumedQuote>,
+
+    // optional only until `init_send_accumulator` runs on an existing deployment
+    #[account(
+        mut,
+        seeds = [SendAccumulator::SEED_PREFIX], bump = send_accumulator.bump
+    )]
+    pub send_accumulator: Option<Box<Account<'info, SendAccumulator>>>,
+
+    /// CHECK: The address check is needed because otherwise
+    /// the supplied Sysvar could be anything else.
+    #[account(address = SYSVAR_INSTRUCTIONS_ID)]
+    pub ix_sysvar: AccountInfo<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+// Anyone can relay a signed message; the payer only covers the rent of the `ReceivedMessage` marker.
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+#[instruction(sender: [u8; 20], target: Pubkey, event_id: u64)]
+pub struct ReceiveMessage<'info> {
+    #[account(
+        constraint = !state.is_paused(PAUSE_RECEIVE) @ CustomError::Paused,
+        constraint = state.version == GlobalState::VERSION @ CustomError::UnsupportedVersion,
+        seeds = [GlobalState::SEED_PREFIX], bump
+    )]
+    pub state: Account<'info, GlobalState>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    // `init` fails if the message was already received
+    #[account(
+        init,
+        payer = payer,
+        space = ReceivedMessage::ACCOUNT_SIZE,
+        seeds = [ReceivedMessage::SEED_PREFIX, event_id.to_le_bytes().as_ref()], bump
+    )]
+    pub received_message: Account<'info, ReceivedMessage>,
+
+    /// CHECK: The address check is needed because otherwise
+    /// the supplied Sysvar could be anything else.
+    #[account(address = SYSVAR_INSTRUCTIONS_ID)]
+    pub ix_sysvar: AccountInfo<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn send_message(
+    ctx: Context<SendMessage>,
+    serialized_args: Vec<u8>,
+    quote_id: u64,
+    max_fee: u64,
+    deadline: u64,
+) -> Result<()> {
+    let args = SendMessagePayload::try_from_slice(&serialized_args)
+        .map_err(|_| error!(CustomError::InvalidSerialization))?;
+    verify_send_signature(&ctx.accounts.ix_sysvar, ctx.accounts.state.send_signer, &serialized_args)?;
+
+    require!(args.sender == ctx.accounts.sender.key(), CustomError::InvalidArgs);
+    require!(args.quote_id == quote_id, CustomError::InvalidArgs);
+    check_quote_terms(args.fee_amount, max_fee, deadline, args.chain_from, args.chain_to, args.timestamp)?;
+    require!(args.payload.len() <= MAX_MESSAGE_PAYLOAD, CustomError::MessageTooLong);
+
+    ctx.accounts.consumed_quote.set_inner(ConsumedQuote {
+        sender: args.sender,
+        expires_at: args.timestamp + SIGNATURE_VALIDITY_TIME,
+        bump: ctx.bumps.consumed_quote,
+    });
+
+    // transfer fee to state PDA
+    transfer_native_from_user(
+        ctx.accounts.sender.to_account_info(),
+        ctx.accounts.state.to_account_info(),
+        args.fee_amount,
+        ctx.accounts.system_program.to_account_info(),
+    )?;
+
+    // update nonce
+    ctx.accounts.state.nonce += 1;
+
+    let event = MessageSent {
+        sender: args.sender,
+        target: args.target,
+        chain_from: SOLANA_CHAIN_ID,
+        chain_to: AMB_CHAIN_ID,
+        event_id: ctx.accounts.state.nonce,
+        payload: args.payload,
+    };
+    accumulate_message(ctx.accounts.send_accumulator.as_mut(), &mut ctx.accounts.state, &event)?;
+    emit_event!(ctx, event);
+    Ok(())
+}
+
+pub fn receive_message(
+    ctx: Context<ReceiveMessage>,
+    sender: [u8; 20],
+    target: Pubkey,
+    event_id: u64,
+    expires_at: u64,
+    payload: Vec<u8>,
+) -> Result<()> {
+    require!(payload.len() <= MAX_MESSAGE_PAYLOAD, CustomError::MessageTooLong);
+    let args = ReceiveMessagePayload {
+        sender,
+        target,
+        chain_from: AMB_CHAIN_ID,
+        chain_to: SOLANA_CHAIN_ID,
+        event_id,
+        expires_at,
+        signer_epoch: ctx.accounts.state.signer_epoch,
+        payload,
+    };
+    let serialized_args = args.signed_message()?;
+    verify_receive_signature(&ctx.accounts.ix_sysvar, ctx.accounts.state.receive_signer, &serialized_args)?;
+    check_receive_expiry(expires_at)?;
+
+    ctx.accounts.received_message.set_inner(ReceivedMessage {
+        event_id,
+        sender,
+        target,
+        payload_hash: hash(&args.payload).to_bytes(),
+        bump: ctx.bumps.received_message,
+    });
+
+    emit_event!(ctx, MessageReceived {
+        sender,
+        target,
+        chain_from: args.chain_from,
+        chain_to: args.chain_to,
+        event_id,
+        payload: args.payload,
+    });
+    Ok(())
+}
@@ -0,0 +1,246 @@
+use crate::instructions::check_timelock_delay;
+use crate::structs::*;
+use crate::utils::events::emit_event;
+use crate::utils::transfer_native_from_user;
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::bpf_loader_upgradeable;
+use anchor_lang::Discriminator;
+use borsh::BorshDeserialize;
+
+// Unversioned layouts, as deployed before `version` and `reserved` were added.
+
+#[derive(BorshDeserialize)]
+pub struct GlobalStateV0 {
+    pub admin: Pubkey,
+    pub send_signer: Pubkey,
+    pub receive_signer: Pubkey,
+    pub nonce: u64,
+    pub pause: bool,
+}
+
+#[derive(BorshDeserialize)]
+pub struct TokenConfigV0 {
+    pub token: Pubkey,
+    pub amb_token: [u8; 20],
+    pub amb_decimals: u8,
+    pub is_mintable: bool,
+    pub bump: u8,
+}
+
+#[derive(BorshDeserialize)]
+pub struct NonceAccountV0 {
+    pub nonce_counter: u64,
+}
+
+impl GlobalStateV0 {
+    pub fn migrate(self, timelock_delay: u64) -> GlobalState {
+        GlobalState {
+            version: GlobalState::VERSION,
+            admin: self.admin,
+            send_signer: self.send_signer,
+            receive_signer: self.receive_signer,
+            nonce: self.nonce,
+            pause_flags: if self.pause { PAUSE_ALL } else { 0 },
+            pause_reason: 0,
+            guardians: vec![],
+            timelock_delay,
+            timelock_nonce: 0,
+            native_send_amount: 0,
+            signer_epoch: 0,
+            send_root: [0; 32],
+            reserved: [0; 16],
+        }
+    }
+}
+
+impl TokenConfigV0 {
+    pub fn migrate(self) -> TokenConfig {
+        TokenConfig::new(self.token, self.amb_token, self.amb_decimals, self.is_mintable, self.bump, false)
+    }
+}
+
+impl NonceAccountV0 {
+    pub fn migrate(self) -> NonceAccount {
+        NonceAccount {
+            version: NonceAccount::VERSION,
+            nonce_counter: self.nonce_counter,
+            reserved: [0; 16],
+        }
+    }
+}
+
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+pub struct MigrateState<'info> {
+    /// CHECK: legacy layout can't be loaded as `GlobalState`; checked in `read_legacy`.
+    #[account(
+        mut,
+        owner = crate::ID,
+        seeds = [GlobalState::SEED_PREFIX], bump
+    )]
+    pub state: UncheckedAccount<'info>,
+
+    // Migrating the state re-initializes it, so it's gated the same way as `initialize`.
+    #[account(mut)]
+    pub upgrade_authority: Signer<'info>,
+
+    #[account(
+        constraint = program_data.upgrade_authority_address == Some(upgrade_authority.key()) @ CustomError::NotUpgradeAuthority,
+        seeds = [crate::ID.as_ref()], bump,
+        seeds::program = bpf_loader_upgradeable::ID,
+    )]
+    pub program_data: Account<'info, ProgramData>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// Permissionless: the migration doesn't change any values, the payer only covers the extra rent.
+#[derive(Accounts)]
+pub struct MigrateAccount<'info> {
+    /// CHECK: legacy layout can't be loaded; checked in `read_legacy`.
+    #[account(mut, owner = crate::ID)]
+    pub account: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn migrate_state(ctx: Context<MigrateState>, timelock_delay: u64) -> Result<()> {
+    check_timelock_delay(timelock_delay)?;
+    let legacy: GlobalStateV0 =
+        read_legacy(&ctx.accounts.state, &GlobalState::DISCRIMINATOR, GlobalState::LEGACY_ACCOUNT_SIZE, GlobalState::VERSION)?;
+
+    write_migrated(
+        &ctx.accounts.state,
+        &ctx.accounts.upgrade_authority,
+        &ctx.accounts.system_program,
+        GlobalState::ACCOUNT_SIZE,
+        &legacy.migrate(timelock_delay),
+    )?;
+
+    emit_event!(ctx, StateMigrated {
+        sender: ctx.accounts.upgrade_authority.key(),
+        version: GlobalState::VERSION,
+        timelock_delay,
+    });
+    Ok(())
+}
+
+pub fn migrate_token_config(ctx: Context<MigrateAccount>) -> Result<()> {
+    let legacy: TokenConfigV0 =
+        read_legacy(&ctx.accounts.account, &TokenConfig::DISCRIMINATOR, TokenConfig::LEGACY_ACCOUNT_SIZE, TokenConfig::VERSION)?;
+
+    write_migrated(
+        &ctx.accounts.account,
+        &ctx.accounts.payer,
+        &ctx.accounts.system_program,
+        TokenConfig::ACCOUNT_SIZE,
+        &legacy.migrate(),
+    )
+}
+
+pub fn migrate_nonce(ctx: Context<MigrateAccount>) -> Result<()> {
+    let legacy: NonceAccountV0 =
+        read_legacy(&ctx.accounts.account, &NonceAccount::DISCRIMINATOR, NonceAccount::LEGACY_ACCOUNT_SIZE, NonceAccount::VERSION)?;
+
+    write_migrated(
+        &ctx.accounts.account,
+        &ctx.accounts.payer,
+        &ctx.accounts.system_program,
+        NonceAccount::ACCOUNT_SIZE,
+        &legacy.migrate(),
+    )
+}
+
+// Legacy accounts are recognized by their exact size; versioned ones keep the version right after the discriminator.
+fn read_legacy<T: BorshDeserialize>(
+    account: &AccountInfo,
+    discriminator: &[u8],
+    legacy_size: usize,
+    version: u8,
+) -> Result<T> {
+    let data = account.try_borrow_data()?;
+    require!(data.len() >= 8 && data[..8] == *discriminator, ErrorCode::AccountDiscriminatorMismatch);
+
+    if data.len() != legacy_size {
+        return match data.get(8) {
+            Some(v) if *v == version => err!(CustomError::AlreadyMigrated),
+            _ => err!(CustomError::UnsupportedVersion),
+        };
+    }
+
+    T::try_from_slice(&data[8..]).map_err(|_| CustomError::InvalidSerialization.into())
+}
+
+fn write_migrated<'info, T: AccountSerialize>(
+    account: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    new_size: usize,
+    migrated: &T,
+) -> Result<()> {
+    let rent = Rent::get()?.minimum_balance(new_size);
+    let lamports = account.lamports();
+    if lamports < rent {
+        transfer_native_from_user(payer.clone(), account.clone(), rent - lamports, system_program.clone())?;
+    }
+
+    account.realloc(new_size, true)?;
+    let mut data = account.try_borrow_mut_data()?;
+    migrated.try_serialize(&mut &mut data[..])
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_migrate_global_state() {
+        let admin = Pubkey::new_unique();
+        let legacy = GlobalStateV0 {
+            admin,
+            send_signer: Pubkey::new_unique(),
+            receive_signer: Pubkey::new_unique(),
+            nonce: 42,
+            pause: true,
+        };
+        let state = legacy.migrate(3600);
+        assert_eq!(state.version, GlobalState::VERSION);
+        assert_eq!(state.admin, admin);
+        assert_eq!(state.nonce, 42);
+        assert_eq!(state.pause_flags, PAUSE_ALL);
+        assert_eq!(state.timelock_delay, 3600);
+        assert!(state.guardians.is_empty());
+    }
+
+    #[test]
+    fn test_migrate_token_config() {
+        let token = Pubkey::new_unique();
+        let legacy = TokenConfigV0 { token, amb_token: [7; 20], amb_decimals: 18, is_mintable: true, bump: 254 };
+        let config = legacy.migrate();
+        assert_eq!(config.version, TokenConfig::VERSION);
+        assert_eq!(config.token, token);
+        assert_eq!(config.amb_token, [7; 20]);
+        assert_eq!(config.amb_decimals, 18);
+        assert!(config.is_mintable);
+        assert_eq!(config.bump, 254);
+        assert!(!config.paused);
+    }
+
+    #[test]
+    fn test_migrated_sizes_fit() {
+        let nonce = NonceAccountV0 { nonce_counter: 5 }.migrate();
+        let mut data = vec![];
+        nonce.try_serialize(&mut data).unwrap();
+        assert_eq!(data.len(), NonceAccount::ACCOUNT_SIZE);
+
+        let legacy = TokenConfigV0 { token: Pubkey::new_unique(), amb_token: [0; 20], amb_decimals: 6, is_mintable: false, bump: 1 };
+        let mut data = vec![];
+        legacy.migrate().try_serialize(&mut data).unwrap();
+        assert_eq!(data.len(), TokenConfig::ACCOUNT_SIZE);
+    }
+}
@@ -1,10 +1,34 @@
+pub mod accumulator;
 pub mod admin;
+pub mod call;
+pub mod message;
+pub mod migrate;
+pub mod nonce;
+pub mod receipt;
+pub mod refund;
 pub mod send;
+pub mod send_batch;
+pub mod send_delegated;
 pub mod receive;
-pub mod utils;
+pub mod receive_batch;
+pub mod receive_to;
+pub mod roles;
+pub mod timelock;
 
 
+pub use accumulator::*;
 pub use admin::*;
+pub use call::*;
+pub use message::*;
+pub use migrate::*;
+pub use nonce::*;
+pub use receipt::*;
+pub use refund::*;
 pub use send::*;
+pub use send_batch::*;
+pub use send_delegated::*;
 pub use receive::*;
-pub use utils::*;
+pub use receive_batch::*;
+pub use receive_to::*;
+pub use roles::*;
+pub use timelock::*;
@@ -0,0 +1,73 @@
+use crate::instructions::verify_receive_signature;
+use crate::structs::*;
+use crate::utils::events::emit_event;
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar::instructions::ID as SYSVAR_INSTRUCTIONS_ID;
+
+// Anyone can relay a signed skip, e.g. support staff unblocking a user.
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+#[instruction(receiver: Pubkey)]
+pub struct SkipNonce<'info> {
+    #[account(
+        constraint = state.version == GlobalState::VERSION @ CustomError::UnsupportedVersion,
+        seeds = [GlobalState::SEED_PREFIX], bump
+    )]
+    pub state: Account<'info, GlobalState>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = NonceAccount::ACCOUNT_SIZE,
+        seeds = [NonceAccount::SEED_PREFIX, receiver.as_ref()], bump,
+    )]
+    pub receiver_nonce_account: Account<'info, NonceAccount>,
+
+    /// CHECK: The address check is needed because otherwise
+    /// the supplied Sysvar could be anything else.
+    #[account(address = SYSVAR_INSTRUCTIONS_ID)]
+    pub ix_sysvar: AccountInfo<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+// Moves the receiver's nonce past receives whose signatures were lost or expired on AMB,
+// so the later ones can be claimed. The voided receives can never be claimed.
+pub fn skip_nonce(ctx: Context<SkipNonce>, receiver: Pubkey, new_nonce: u64, voided_event_ids: Vec<u64>) -> Result<()> {
+    let nonce = &mut ctx.accounts.receiver_nonce_account;
+    // version is 0 only if the account was just created by `init_if_needed`
+    if nonce.version == 0 {
+        nonce.version = NonceAccount::VERSION;
+    }
+    require!(nonce.version == NonceAccount::VERSION, CustomError::UnsupportedVersion);
+
+    let args = SkipNoncePayload {
+        receiver,
+        current_nonce: nonce.nonce_counter,
+        new_nonce,
+        voided_event_ids,
+        chain_from: AMB_CHAIN_ID,
+        chain_to: SOLANA_CHAIN_ID,
+    };
+    let serialized_args = args.signed_message()?;
+    verify_receive_signature(&ctx.accounts.ix_sysvar, ctx.accounts.state.receive_signer, &serialized_args)?;
+
+    require!(args.new_nonce > args.current_nonce, CustomError::InvalidNonce);
+    require!(args.voided_event_ids.len() <= MAX_SKIPPED_NONCES, CustomError::InvalidArgs);
+    require!(
+        args.voided_event_ids.len() as u64 == args.new_nonce - args.current_nonce,
+        CustomError::InvalidArgs
+    );
+
+    nonce.nonce_counter = args.new_nonce;
+
+    emit_event!(ctx, NonceSkipped {
+        receiver,
+        from_nonce: args.current_nonce,
+        to_nonce: args.new_nonce,
+        voided_event_ids: args.voided_event_ids,
+    });
+    Ok(())
+}
@@ -0,0 +1,101 @@
+use crate::instructions::verify_receive_signature;
+use crate::structs::*;
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar::instructions::ID as SYSVAR_INSTRUCTIONS_ID;
+use anchor_lang::system_program::{self, Allocate, Assign, CreateAccount, Transfer};
+
+// Permissionless: anyone can close a receipt once the AMB side claimed it, returning the rent to the sender.
+#[derive(Accounts)]
+#[instruction(event_id: u64)]
+pub struct CloseSendReceipt<'info> {
+    #[account(
+        constraint = state.version == GlobalState::VERSION @ CustomError::UnsupportedVersion,
+        seeds = [GlobalState::SEED_PREFIX], bump
+    )]
+    pub state: Account<'info, GlobalState>,
+
+    #[account(
+        mut,
+        close = sender,
+        has_one = sender,
+        seeds = [SendReceipt::SEED_PREFIX, event_id.to_le_bytes().as_ref()], bump = receipt.bump
+    )]
+    pub receipt: Box<Account<'info, SendReceipt>>,
+
+    /// CHECK: only receives the rent back; checked by `has_one` on `receipt`.
+    #[account(mut)]
+    pub sender: UncheckedAccount<'info>,
+
+    /// CHECK: The address check is needed because otherwise
+    /// the supplied Sysvar could be anything else.
+    #[account(address = SYSVAR_INSTRUCTIONS_ID)]
+    pub ix_sysvar: AccountInfo<'info>,
+}
+
+pub fn close_send_receipt(ctx: Context<CloseSendReceipt>, event_id: u64) -> Result<()> {
+    let args = ReceiptClaimedPayload {
+        event_id,
+        receipt_hash: ctx.accounts.receipt.hash,
+        chain_from: SOLANA_CHAIN_ID,
+        chain_to: AMB_CHAIN_ID,
+    };
+    let serialized_args = args.signed_message()?;
+    verify_receive_signature(&ctx.accounts.ix_sysvar, ctx.accounts.state.receive_signer, &serialized_args)?;
+    Ok(())
+}
+
+// Stores the send in its receipt, if the sender asked for one.
+pub fn store_send_receipt(receipt: Option<&mut Box<Account<SendReceipt>>>, bump: Option<u8>, sender: Pubkey, event: &SendEvent) {
+    if let (Some(receipt), Some(bump)) = (receipt, bump) {
+        receipt.set_inner(SendReceipt::new(sender, event, bump));
+    }
+}
+
+// Same as `store_send_receipt` for a receipt passed as a remaining account (`send_batch` legs),
+// creating it the way `init` does: topping up an account that was already funded instead of failing.
+pub fn create_send_receipt<'info>(
+    receipt_info: &'info AccountInfo<'info>,
+    sender: AccountInfo<'info>,
+    system_program: AccountInfo<'info>,
+    program_id: &Pubkey,
+    event: &SendEvent,
+) -> Result<()> {
+    let event_id = event.event_id.to_le_bytes();
+    let (receipt_pda, bump) = Pubkey::find_program_address(&[SendReceipt::SEED_PREFIX, &event_id], program_id);
+    require!(receipt_info.key() == receipt_pda, CustomError::InvalidArgs);
+
+    let signer_seeds: &[&[&[u8]]] = &[&[SendReceipt::SEED_PREFIX, &event_id, &[bump]]];
+    let rent = Rent::get()?.minimum_balance(SendReceipt::ACCOUNT_SIZE);
+    if receipt_info.lamports() == 0 {
+        system_program::create_account(
+            CpiContext::new_with_signer(
+                system_program,
+                CreateAccount { from: sender, to: receipt_info.clone() },
+                signer_seeds,
+            ),
+            rent,
+            SendReceipt::ACCOUNT_SIZE as u64,
+            program_id,
+        )?;
+    } else {
+        let top_up = rent.saturating_sub(receipt_info.lamports());
+        if top_up > 0 {
+            system_program::transfer(
+                CpiContext::new(system_program.clone(), Transfer { from: sender, to: receipt_info.clone() }),
+                top_up,
+            )?;
+        }
+        system_program::allocate(
+            CpiContext::new_with_signer(system_program.clone(), Allocate { account_to_allocate: receipt_info.clone() }, signer_seeds),
+            SendReceipt::ACCOUNT_SIZE as u64,
+        )?;
+        system_program::assign(
+            CpiContext::new_with_signer(system_program, Assign { account_to_assign: receipt_info.clone() }, signer_seeds),
+            program_id,
+        )?;
+    }
+
+    let mut receipt = Account::<SendReceipt>::try_from_unchecked(receipt_info)?;
+    receipt.set_inner(SendReceipt::new(event.from, event, bump));
+    receipt.exit(program_id)
+}
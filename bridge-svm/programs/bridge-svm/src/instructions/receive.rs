@@ -1,20 +1,23 @@
 use crate::structs::*;
+use crate::utils::ed25519::find_ed25519_signatures;
+use crate::utils::events::emit_event;
+use crate::utils::flags::{BridgeFlags, ReceiveFlagData, TAG_TO_TOKEN_ACCOUNT};
 use crate::utils::transfer::{mint_spl_to_user, transfer_spl_to_user};
 use anchor_lang::prelude::*;
-use anchor_lang::solana_program::{
-    keccak::hash,
-    sysvar::instructions::{get_instruction_relative, ID as SYSVAR_INSTRUCTIONS_ID},
-    ed25519_program::ID as ED25519_ID
-};
+use anchor_lang::solana_program::{keccak::hash, sysvar::instructions::ID as SYSVAR_INSTRUCTIONS_ID};
 use anchor_spl::associated_token::AssociatedToken;
 use anchor_spl::token::Token;
 use anchor_spl::token_interface::{Mint, TokenAccount};
 
 
+#[cfg_attr(feature = "event-cpi", event_cpi)]
 #[derive(Accounts)]
 pub struct Receive<'info> {
+    // mut for the native drop-off from the vault
     #[account(
-        constraint = !state.pause @ CustomError::Paused,
+        mut,
+        constraint = !state.is_paused(PAUSE_RECEIVE) @ CustomError::Paused,
+        constraint = state.version == GlobalState::VERSION @ CustomError::UnsupportedVersion,
         seeds = [GlobalState::SEED_PREFIX], bump
     )]
     pub state: Account<'info, GlobalState>,
@@ -40,6 +43,9 @@ pub struct Receive<'info> {
     pub receiver_nonce_account: Account<'info, NonceAccount>,
 
     #[account(
+        constraint = !bridge_token.is_migrated() @ CustomError::TokenMigrated,
+        constraint = !bridge_token.paused @ CustomError::TokenPaused,
+        constraint = bridge_token.version == TokenConfig::VERSION @ CustomError::UnsupportedVersion,
         seeds = [TokenConfig::SEED_PREFIX, mint.key().as_ref()], bump = bridge_token.bump
     )]
     pub bridge_token: Account<'info, TokenConfig>,
@@ -69,12 +75,11 @@ pub fn receive(
     ctx: Context<Receive>,
     amount_to: u64,
     event_id: u64,
+    expires_at: u64,
+    source: SourceRef,
     flags: [u8; 32],
     flag_data: Vec<u8>,
 ) -> Result<()> {
-    let nonce = &mut ctx.accounts.receiver_nonce_account;
-    let bridge_token = &ctx.accounts.bridge_token;
-
     let args = ReceivePayload{
         to: *ctx.accounts.receiver.key,
         token_address_to: ctx.accounts.mint.key(),
@@ -82,70 +87,145 @@ pub fn receive(
         chain_from: AMB_CHAIN_ID,
         chain_to: SOLANA_CHAIN_ID,
         event_id,
+        expires_at,
+        signer_epoch: ctx.accounts.state.signer_epoch,
+        source,
         flags,
         flag_data,
     };
-    let serialized_args = args.try_to_vec().map_err(|_| CustomError::InvalidSerialization)?;
-    let args_hash = hash(&serialized_args);
+    let serialized_args = args.signed_message()?;
+    verify_receive_signature(&ctx.accounts.ix_sysvar, ctx.accounts.state.receive_signer, &serialized_args)?;
+    check_receive_expiry(args.expires_at)?;
 
-    // check signature
-    let ix = get_instruction_relative(-1, &ctx.accounts.ix_sysvar.to_account_info())?;
-    require!(ix.program_id == ED25519_ID, CustomError::InvalidSignature);
+    require!(
+        ctx.accounts.mint.key() == args.token_address_to,
+        CustomError::InvalidArgs
+    );
 
-    let ix_data_len = ix.data.len();
-    let num_signatures = (ix_data_len - 32 - 2) / (64+32+14);  // each signature block is 64+32+14 bytes, plus 32 bytes message, plus 2 bytes header
+    let flags = BridgeFlags::from_bytes(&args.flags).for_receive()?;
+    let flag_data = ReceiveFlagData::parse(&args.flag_data)?;
+    // the signer is the owner here, payloads to a token account go through `receive_to`
+    require!(!flag_data.data.has(TAG_TO_TOKEN_ACCOUNT), CustomError::InvalidFlagData);
+    use_receiver_nonce(&mut ctx.accounts.receiver_nonce_account, flag_data.nonce)?;
+
+    give_tokens_to(
+        &ctx.accounts.bridge_token,
+        ctx.accounts.bridge_token_account.as_ref().map(|account| account.to_account_info()),
+        ctx.accounts.receiver_token_account.to_account_info(),
+        &ctx.accounts.mint,
+        args.amount_to,
+        ctx.accounts.token_program.to_account_info(),
+    )?;
+
+    if flags.send_native_to_receiver {
+        send_native_to_receiver(&ctx.accounts.state, &ctx.accounts.receiver.to_account_info())?;
+    }
 
-    let signed_message = &ix.data[ix_data_len.saturating_sub(32)..];
-    let signer_pubkeys = &ix.data[ix_data_len.saturating_sub(32 * num_signatures + 32)..ix_data_len.saturating_sub(32)];
+    // event
+    emit_event!(ctx, receive_event(args)?);
 
-    let signer_pubkey = hash(signer_pubkeys);
+    Ok(())
+}
 
-    require!(
-        signed_message == args_hash.to_bytes(),
-        CustomError::InvalidSignature
-    );
-    require!(
-        signer_pubkey.to_bytes() == ctx.accounts.state.receive_signer.to_bytes(),
-        CustomError::InvalidSignature
-    );
+// `ReceiveEventV2` for a delivered receive, `args` as signed.
+pub fn receive_event(args: ReceivePayload) -> Result<ReceiveEventV2> {
+    Ok(ReceiveEventV2 {
+        schema_version: EVENT_SCHEMA_VERSION,
+        to: args.to,
+        token_address_to: args.token_address_to,
+        amount_to: args.amount_to,
+        chain_from: args.chain_from,
+        chain_to: args.chain_to,
+        event_id: args.event_id,
+        expires_at: args.expires_at,
+        signer_epoch: args.signer_epoch,
+        flags: args.flags,
+        flag_data: args.flag_data,
+        receipt_hash: args.source.receipt_hash,
+        source_tx_hash: args.source.tx_hash,
+        slot: Clock::get()?.slot,
+    })
+}
 
-    require!(
-        ctx.accounts.mint.key() == args.token_address_to,
-        CustomError::InvalidArgs
-    );
+// Receive payloads are valid until `expires_at`. They also commit to the current `signer_epoch`,
+// so ones signed before a signer change fail the signature check.
+pub fn check_receive_expiry(expires_at: u64) -> Result<()> {
+    require!((Clock::get()?.unix_timestamp as u64) <= expires_at, CustomError::PayloadExpired);
+    Ok(())
+}
+
+// `nonce` is the receiver's expected nonce from `flag_data`; each receive uses it up.
+pub fn use_receiver_nonce(nonce: &mut NonceAccount, args_nonce: u64) -> Result<()> {
+    // version is 0 only if the account was just created by `init_if_needed`
+    if nonce.version == 0 {
+        nonce.version = NonceAccount::VERSION;
+    }
+    require!(nonce.version == NonceAccount::VERSION, CustomError::UnsupportedVersion);
 
-    let args_nonce = u64::from_be_bytes(args.flag_data[0..8].try_into().unwrap());
     require!(args_nonce == nonce.nonce_counter, CustomError::InvalidNonce);
 
-    if ctx.accounts.bridge_token.is_mintable {
+    // update user nonce
+    nonce.nonce_counter += 1;
+    Ok(())
+}
+
+// Pays `native_send_amount` lamports from the fee vault, for receives that set `SEND_NATIVE_TO_RECEIVER`.
+pub fn send_native_to_receiver(state: &Account<GlobalState>, receiver: &AccountInfo) -> Result<()> {
+    let amount = state.native_send_amount;
+    if amount == 0 {
+        return Ok(());
+    }
+
+    // the vault must stay rent exempt
+    let vault = state.to_account_info();
+    let rent = Rent::get()?.minimum_balance(vault.data_len());
+    require!(vault.lamports() >= rent + amount, CustomError::VaultInsufficient);
+
+    **vault.try_borrow_mut_lamports()? -= amount;
+    **receiver.try_borrow_mut_lamports()? += amount;
+    Ok(())
+}
+
+// Mints synthetic tokens, or releases the others from the bridge token account.
+pub fn give_tokens_to<'info>(
+    bridge_token: &Account<'info, TokenConfig>,
+    bridge_token_account: Option<AccountInfo<'info>>,
+    to: AccountInfo<'info>,
+    mint: &InterfaceAccount<'info, Mint>,
+    amount: u64,
+    token_program: AccountInfo<'info>,
+) -> Result<()> {
+    if bridge_token.is_mintable {
         mint_spl_to_user(
-            ctx.accounts.bridge_token.to_account_info(),
-            ctx.accounts.receiver_token_account.to_account_info(),
-            ctx.accounts.mint.to_account_info(),
-            args.amount_to,
-            ctx.accounts.token_program.to_account_info(),
+            bridge_token.to_account_info(),
+            to,
+            mint.to_account_info(),
+            amount,
+            token_program,
             bridge_token.clone().into_inner(),
-        )?;
+        )
     } else {
         transfer_spl_to_user(
-            ctx.accounts.bridge_token.to_account_info(),
-            ctx.accounts
-                .bridge_token_account
-                .clone()
-                .expect("no bridge ata")
-                .to_account_info(),
-            ctx.accounts.receiver_token_account.to_account_info(),
-            args.amount_to,
-            ctx.accounts.token_program.to_account_info(),
+            bridge_token.to_account_info(),
+            bridge_token_account.expect("no bridge ata"),
+            to,
+            mint.to_account_info(),
+            mint.decimals,
+            amount,
+            token_program,
             bridge_token.clone().into_inner(),
-        )?;
+        )
     }
+}
 
-    // update user nonce
-    nonce.nonce_counter += 1;
-
-    // event
-    emit!(args);
-
-    Ok(())
+// A precompile instruction anywhere in the transaction must hold the receive signers' Ed25519 signatures
+// over `keccak(serialized_args)`, see `ReceiveSigned`; `receive_signer` is the keccak of their concatenated pubkeys.
+pub fn verify_receive_signature(ix_sysvar: &AccountInfo, receive_signer: Pubkey, serialized_args: &[u8]) -> Result<()> {
+    let message = hash(serialized_args).to_bytes();
+    find_ed25519_signatures(ix_sysvar, |signatures| {
+        let signers: Vec<u8> = signatures.iter().flat_map(|(signer, _)| signer.to_bytes()).collect();
+        !signatures.is_empty()
+            && signatures.iter().all(|(_, signed)| *signed == message)
+            && hash(&signers).to_bytes() == receive_signer.to_bytes()
+    })
 }
@@ -0,0 +1,141 @@
+use crate::instructions::{check_receive_expiry, give_tokens_to, send_native_to_receiver, receive_event, use_receiver_nonce, verify_receive_signature};
+use crate::structs::*;
+use crate::utils::events::emit_event;
+use crate::utils::flags::{BridgeFlags, ReceiveFlagData, TAG_TO_TOKEN_ACCOUNT};
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{keccak::hash, sysvar::instructions::ID as SYSVAR_INSTRUCTIONS_ID};
+use anchor_spl::associated_token::get_associated_token_address;
+use anchor_spl::token::Token;
+use anchor_spl::token_interface::Mint;
+
+// Remaining accounts, for each leg in order: mint (mut), bridge_token, receiver_token_account (mut, must exist),
+// bridge_token_account (mut; bridge program id for mintable tokens).
+pub const ACCOUNTS_PER_RECEIVE_LEG: usize = 4;
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+pub struct ReceiveBatch<'info> {
+    #[account(
+        mut,
+        constraint = !state.is_paused(PAUSE_RECEIVE) @ CustomError::Paused,
+        constraint = state.version == GlobalState::VERSION @ CustomError::UnsupportedVersion,
+        seeds = [GlobalState::SEED_PREFIX], bump
+    )]
+    pub state: Account<'info, GlobalState>,
+
+    #[account(mut)]
+    pub receiver: Signer<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = receiver,
+        space = NonceAccount::ACCOUNT_SIZE,
+        seeds = [NonceAccount::SEED_PREFIX, receiver.key().as_ref()], bump,
+    )]
+    pub receiver_nonce_account: Account<'info, NonceAccount>,
+
+    /// CHECK: The address check is needed because otherwise
+    /// the supplied Sysvar could be anything else.
+    #[account(address = SYSVAR_INSTRUCTIONS_ID)]
+    pub ix_sysvar: AccountInfo<'info>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+// The receive signers sign once over `keccak(keccak(payload_1) || ... || keccak(payload_n))`,
+// where each payload is the leg's full `ReceivePayload`.
+pub fn receive_batch<'info>(
+    ctx: Context<'_, '_, 'info, 'info, ReceiveBatch<'info>>,
+    legs: Vec<ReceiveLeg>,
+) -> Result<()> {
+    require!(!legs.is_empty() && legs.len() <= MAX_BATCH_LEGS, CustomError::InvalidArgs);
+    require!(ctx.remaining_accounts.len() == legs.len() * ACCOUNTS_PER_RECEIVE_LEG, CustomError::InvalidArgs);
+
+    let receiver = ctx.accounts.receiver.key();
+    let signer_epoch = ctx.accounts.state.signer_epoch;
+    let payloads = legs
+        .into_iter()
+        .zip(ctx.remaining_accounts.chunks_exact(ACCOUNTS_PER_RECEIVE_LEG))
+        .map(|(leg, accounts)| ReceivePayload {
+            to: receiver,
+            token_address_to: accounts[0].key(),
+            amount_to: leg.amount_to,
+            chain_from: AMB_CHAIN_ID,
+            chain_to: SOLANA_CHAIN_ID,
+            event_id: leg.event_id,
+            expires_at: leg.expires_at,
+            signer_epoch,
+            source: leg.source,
+            flags: leg.flags,
+            flag_data: leg.flag_data,
+        })
+        .collect::<Vec<_>>();
+    verify_receive_signature(&ctx.accounts.ix_sysvar, ctx.accounts.state.receive_signer, &batch_message(&payloads)?)?;
+
+    for (args, accounts) in payloads.into_iter().zip(ctx.remaining_accounts.chunks_exact(ACCOUNTS_PER_RECEIVE_LEG)) {
+        let [mint_info, bridge_token_info, receiver_token_account_info, bridge_token_account_info] = accounts else {
+            unreachable!()
+        };
+        check_receive_expiry(args.expires_at)?;
+
+        // the nonces of the legs follow each other, so every event can be claimed once
+        let flags = BridgeFlags::from_bytes(&args.flags).for_receive()?;
+        let flag_data = ReceiveFlagData::parse(&args.flag_data)?;
+        require!(!flag_data.data.has(TAG_TO_TOKEN_ACCOUNT), CustomError::InvalidFlagData);
+        use_receiver_nonce(&mut ctx.accounts.receiver_nonce_account, flag_data.nonce)?;
+
+        let mint = InterfaceAccount::<Mint>::try_from(mint_info)?;
+        let bridge_token = Account::<TokenConfig>::try_from(bridge_token_info)?;
+        let bridge_token_pda = Pubkey::create_program_address(
+            &[TokenConfig::SEED_PREFIX, mint.key().as_ref(), &[bridge_token.bump]],
+            ctx.program_id,
+        )
+        .map_err(|_| error!(CustomError::InvalidToken))?;
+        require!(bridge_token.key() == bridge_token_pda, CustomError::InvalidToken);
+        require!(bridge_token.version == TokenConfig::VERSION, CustomError::UnsupportedVersion);
+        require!(!bridge_token.is_migrated(), CustomError::TokenMigrated);
+        require!(!bridge_token.paused, CustomError::TokenPaused);
+
+        require!(
+            receiver_token_account_info.key() == get_associated_token_address(&receiver, &mint.key()),
+            CustomError::InvalidArgs
+        );
+        let bridge_token_account = if bridge_token.is_mintable {
+            None
+        } else {
+            require!(
+                bridge_token_account_info.key() == get_associated_token_address(&bridge_token.key(), &mint.key()),
+                CustomError::InvalidArgs
+            );
+            Some(bridge_token_account_info.clone())
+        };
+
+        give_tokens_to(
+            &bridge_token,
+            bridge_token_account,
+            receiver_token_account_info.clone(),
+            &mint,
+            args.amount_to,
+            ctx.accounts.token_program.to_account_info(),
+        )?;
+
+        if flags.send_native_to_receiver {
+            send_native_to_receiver(&ctx.accounts.state, &ctx.accounts.receiver.to_account_info())?;
+        }
+
+        // event, one per leg
+        emit_event!(ctx, receive_event(args)?);
+    }
+    Ok(())
+}
+
+// `RECEIVE_BATCH_TAG` and the concatenated hashes of the legs' signed messages;
+// `verify_receive_signature` hashes it once more.
+pub fn batch_message(payloads: &[ReceivePayload]) -> Result<Vec<u8>> {
+    let mut message = Vec::with_capacity(8 + payloads.len() * 32);
+    message.extend_from_slice(&RECEIVE_BATCH_TAG);
+    for payload in payloads {
+        message.extend_from_slice(&hash(&payload.signed_message()?).to_bytes());
+    }
+    Ok(message)
+}
@@ -0,0 +1,148 @@
+use crate::instructions::{check_receive_expiry, give_tokens_to, send_native_to_receiver, receive_event, use_receiver_nonce, verify_receive_signature};
+use crate::structs::*;
+use crate::utils::events::emit_event;
+use crate::utils::flags::{BridgeFlags, ReceiveFlagData, TAG_TO_TOKEN_ACCOUNT};
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar::instructions::ID as SYSVAR_INSTRUCTIONS_ID;
+use anchor_spl::associated_token::{self, get_associated_token_address, AssociatedToken};
+use anchor_spl::token::Token;
+use anchor_spl::token_interface::{Mint, TokenAccount};
+
+// Receive for a `to` that can't sign: a PDA owner (program vaults, multisigs) or, if the payload has
+// the `TAG_TO_TOKEN_ACCOUNT` entry, a specific token account. Anyone can relay it; `payer` covers the rent.
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+#[instruction(to: Pubkey)]
+pub struct ReceiveTo<'info> {
+    // mut for the native drop-off from the vault
+    #[account(
+        mut,
+        constraint = !state.is_paused(PAUSE_RECEIVE) @ CustomError::Paused,
+        constraint = state.version == GlobalState::VERSION @ CustomError::UnsupportedVersion,
+        seeds = [GlobalState::SEED_PREFIX], bump
+    )]
+    pub state: Box<Account<'info, GlobalState>>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// CHECK: `ReceivePayload.to`, covered by the signature; gets the native drop-off.
+    #[account(mut, address = to)]
+    pub recipient: UncheckedAccount<'info>,
+
+    /// CHECK: `recipient` itself, or the ATA of `recipient` (created if missing); checked in `receive_to`.
+    #[account(mut)]
+    pub receiver_token_account: UncheckedAccount<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = NonceAccount::ACCOUNT_SIZE,
+        seeds = [NonceAccount::SEED_PREFIX, to.as_ref()], bump,
+    )]
+    pub receiver_nonce_account: Box<Account<'info, NonceAccount>>,
+
+    #[account(
+        constraint = !bridge_token.is_migrated() @ CustomError::TokenMigrated,
+        constraint = !bridge_token.paused @ CustomError::TokenPaused,
+        constraint = bridge_token.version == TokenConfig::VERSION @ CustomError::UnsupportedVersion,
+        seeds = [TokenConfig::SEED_PREFIX, mint.key().as_ref()], bump = bridge_token.bump
+    )]
+    pub bridge_token: Box<Account<'info, TokenConfig>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = bridge_token,
+    )]
+    pub bridge_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    #[account(mut)]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// CHECK: The address check is needed because otherwise
+    /// the supplied Sysvar could be anything else.
+    #[account(address = SYSVAR_INSTRUCTIONS_ID)]
+    pub ix_sysvar: AccountInfo<'info>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+pub fn receive_to(
+    ctx: Context<ReceiveTo>,
+    to: Pubkey,
+    amount_to: u64,
+    event_id: u64,
+    expires_at: u64,
+    source: SourceRef,
+    flags: [u8; 32],
+    flag_data: Vec<u8>,
+) -> Result<()> {
+    let args = ReceivePayload {
+        to,
+        token_address_to: ctx.accounts.mint.key(),
+        amount_to,
+        chain_from: AMB_CHAIN_ID,
+        chain_to: SOLANA_CHAIN_ID,
+        event_id,
+        expires_at,
+        signer_epoch: ctx.accounts.state.signer_epoch,
+        source,
+        flags,
+        flag_data,
+    };
+    let serialized_args = args.signed_message()?;
+    verify_receive_signature(&ctx.accounts.ix_sysvar, ctx.accounts.state.receive_signer, &serialized_args)?;
+    check_receive_expiry(args.expires_at)?;
+
+    let flags = BridgeFlags::from_bytes(&args.flags).for_receive()?;
+    let flag_data = ReceiveFlagData::parse(&args.flag_data)?;
+    use_receiver_nonce(&mut ctx.accounts.receiver_nonce_account, flag_data.nonce)?;
+
+    let to_token_account = flag_data.data.has(TAG_TO_TOKEN_ACCOUNT);
+    if to_token_account {
+        require!(ctx.accounts.receiver_token_account.key() == to, CustomError::InvalidArgs);
+        // lamports sent to a token account would be stuck there
+        require!(!flags.send_native_to_receiver, CustomError::UnsupportedFlags);
+    } else {
+        // works for off-curve owners too
+        require!(
+            ctx.accounts.receiver_token_account.key() == get_associated_token_address(&to, &ctx.accounts.mint.key()),
+            CustomError::InvalidArgs
+        );
+        associated_token::create_idempotent(CpiContext::new(
+            ctx.accounts.associated_token_program.to_account_info(),
+            associated_token::Create {
+                payer: ctx.accounts.payer.to_account_info(),
+                associated_token: ctx.accounts.receiver_token_account.to_account_info(),
+                authority: ctx.accounts.recipient.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                system_program: ctx.accounts.system_program.to_account_info(),
+                token_program: ctx.accounts.token_program.to_account_info(),
+            },
+        ))?;
+    }
+
+    let receiver_token_account_info = ctx.accounts.receiver_token_account.to_account_info();
+    require!(receiver_token_account_info.owner == &ctx.accounts.token_program.key(), CustomError::InvalidArgs);
+    let receiver_token_account = TokenAccount::try_deserialize(&mut &receiver_token_account_info.try_borrow_data()?[..])?;
+    require!(receiver_token_account.mint == ctx.accounts.mint.key(), CustomError::InvalidArgs);
+
+    give_tokens_to(
+        &ctx.accounts.bridge_token,
+        ctx.accounts.bridge_token_account.as_ref().map(|account| account.to_account_info()),
+        receiver_token_account_info,
+        &ctx.accounts.mint,
+        args.amount_to,
+        ctx.accounts.token_program.to_account_info(),
+    )?;
+
+    if flags.send_native_to_receiver {
+        send_native_to_receiver(&ctx.accounts.state, &ctx.accounts.recipient.to_account_info())?;
+    }
+
+    // event
+    emit_event!(ctx, receive_event(args)?);
+    Ok(())
+}
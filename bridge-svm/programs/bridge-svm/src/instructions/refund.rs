@@ -0,0 +1,115 @@
+use crate::instructions::{give_tokens_to, verify_receive_signature};
+use crate::structs::*;
+use crate::utils::events::emit_event;
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar::instructions::ID as SYSVAR_INSTRUCTIONS_ID;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token::Token;
+use anchor_spl::token_interface::{Mint, TokenAccount};
+
+// Anyone can relay a signed refund; the payer covers the rent of the `RefundedSend` marker and the sender's ATA.
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+#[instruction(event_id: u64)]
+pub struct Refund<'info> {
+    #[account(
+        constraint = !state.is_paused(PAUSE_RECEIVE) @ CustomError::Paused,
+        constraint = state.version == GlobalState::VERSION @ CustomError::UnsupportedVersion,
+        seeds = [GlobalState::SEED_PREFIX], bump
+    )]
+    pub state: Box<Account<'info, GlobalState>>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// CHECK: sender of the refunded send, covered by the signature.
+    pub sender: UncheckedAccount<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = mint,
+        associated_token::authority = sender,
+        associated_token::token_program = token_program,
+    )]
+    pub sender_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    // `init` fails if the send was already refunded
+    #[account(
+        init,
+        payer = payer,
+        space = RefundedSend::ACCOUNT_SIZE,
+        seeds = [RefundedSend::SEED_PREFIX, event_id.to_le_bytes().as_ref()], bump
+    )]
+    pub refunded_send: Box<Account<'info, RefundedSend>>,
+
+    // Refunds are signed by the validators, so a paused token still pays them out.
+    // A migrated token can't be minted here anymore: its pending refunds are paid by the successor,
+    // which skips the sends that have a `RefundedSend` marker here.
+    #[account(
+        constraint = !bridge_token.is_migrated() @ CustomError::TokenMigrated,
+        constraint = bridge_token.version == TokenConfig::VERSION @ CustomError::UnsupportedVersion,
+        seeds = [TokenConfig::SEED_PREFIX, mint.key().as_ref()], bump = bridge_token.bump
+    )]
+    pub bridge_token: Box<Account<'info, TokenConfig>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = bridge_token,
+    )]
+    pub bridge_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    #[account(mut)]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// CHECK: The address check is needed because otherwise
+    /// the supplied Sysvar could be anything else.
+    #[account(address = SYSVAR_INSTRUCTIONS_ID)]
+    pub ix_sysvar: AccountInfo<'info>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+// Gives back exactly what the send took: unlocks the tokens from the vault, or re-mints the burned synthetic ones.
+pub fn refund(ctx: Context<Refund>, event_id: u64, amount: u64) -> Result<()> {
+    let args = RefundPayload {
+        event_id,
+        sender: ctx.accounts.sender.key(),
+        token_address: ctx.accounts.mint.key(),
+        amount,
+        chain_from: SOLANA_CHAIN_ID,
+        chain_to: AMB_CHAIN_ID,
+    };
+    let serialized_args = args.signed_message()?;
+    verify_receive_signature(&ctx.accounts.ix_sysvar, ctx.accounts.state.receive_signer, &serialized_args)?;
+
+    // only sends that already happened can be refunded
+    require!(event_id <= ctx.accounts.state.nonce, CustomError::InvalidArgs);
+
+    ctx.accounts.refunded_send.set_inner(RefundedSend {
+        event_id,
+        sender: args.sender,
+        token: args.token_address,
+        amount,
+        bump: ctx.bumps.refunded_send,
+    });
+
+    give_tokens_to(
+        &ctx.accounts.bridge_token,
+        ctx.accounts.bridge_token_account.as_ref().map(|account| account.to_account_info()),
+        ctx.accounts.sender_token_account.to_account_info(),
+        &ctx.accounts.mint,
+        amount,
+        ctx.accounts.token_program.to_account_info(),
+    )?;
+
+    emit_event!(ctx, SendRefunded {
+        event_id,
+        sender: args.sender,
+        token: args.token_address,
+        amount,
+    });
+    Ok(())
+}
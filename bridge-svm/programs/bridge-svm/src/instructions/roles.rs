@@ -0,0 +1,86 @@
+use crate::structs::*;
+use crate::utils::events::emit_event;
+use anchor_lang::prelude::*;
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+#[instruction(member: Pubkey)]
+pub struct ManageRole<'info> {
+    #[account(
+        has_one = admin @ CustomError::NotAdmin,
+        constraint = state.version == GlobalState::VERSION @ CustomError::UnsupportedVersion,
+        seeds = [GlobalState::SEED_PREFIX], bump
+    )]
+    pub state: Account<'info, GlobalState>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = RoleAccount::ACCOUNT_SIZE,
+        seeds = [RoleAccount::SEED_PREFIX, member.as_ref()], bump
+    )]
+    pub role_account: Account<'info, RoleAccount>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// Revoking doesn't create the role account, the member must hold the role.
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+#[instruction(member: Pubkey)]
+pub struct RevokeRole<'info> {
+    #[account(
+        has_one = admin @ CustomError::NotAdmin,
+        constraint = state.version == GlobalState::VERSION @ CustomError::UnsupportedVersion,
+        seeds = [GlobalState::SEED_PREFIX], bump
+    )]
+    pub state: Account<'info, GlobalState>,
+
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [RoleAccount::SEED_PREFIX, member.as_ref()], bump = role_account.bump
+    )]
+    pub role_account: Account<'info, RoleAccount>,
+}
+
+// Super-admin (`state.admin`) passes every role check; other signers need the role in their `RoleAccount`.
+pub fn has_role(
+    state: &GlobalState,
+    authority: &Pubkey,
+    authority_roles: Option<&RoleAccount>,
+    role: Role,
+) -> bool {
+    *authority == state.admin || authority_roles.is_some_and(|roles| roles.has_role(role))
+}
+
+pub fn grant_role(ctx: Context<ManageRole>, member: Pubkey, role: Role) -> Result<()> {
+    let role_account = &mut ctx.accounts.role_account;
+    role_account.member = member;
+    role_account.roles |= role.mask();
+    role_account.bump = ctx.bumps.role_account;
+
+    emit_event!(ctx, RoleGranted {
+        member,
+        role,
+        sender: ctx.accounts.admin.key(),
+    });
+    Ok(())
+}
+
+pub fn revoke_role(ctx: Context<RevokeRole>, member: Pubkey, role: Role) -> Result<()> {
+    let role_account = &mut ctx.accounts.role_account;
+    require!(role_account.has_role(role), CustomError::MissingRole);
+    role_account.roles &= !role.mask();
+
+    emit_event!(ctx, RoleRevoked {
+        member,
+        role,
+        sender: ctx.accounts.admin.key(),
+    });
+    Ok(())
+}
@@ -1,23 +1,26 @@
+use crate::instructions::{accumulate_send, store_send_receipt};
 use crate::structs::*;
+use crate::utils::ed25519::find_ed25519_signatures;
+use crate::utils::events::emit_event;
+use crate::utils::flags::check_send_flags;
 use crate::utils::scale_amount;
 use crate::utils::transfer::{
     burn_spl_from_user, transfer_native_from_user, transfer_spl_from_user,
 };
 use anchor_lang::prelude::*;
-use anchor_lang::solana_program::{
-    keccak::hash,
-    sysvar::instructions::{get_instruction_relative, ID as SYSVAR_INSTRUCTIONS_ID},
-    ed25519_program::ID as ED25519_ID
-};
+use anchor_lang::solana_program::{keccak::hash, sysvar::instructions::ID as SYSVAR_INSTRUCTIONS_ID};
 use anchor_spl::token::Token;
 use anchor_spl::token_interface::{Mint, TokenAccount};
 
 
+#[cfg_attr(feature = "event-cpi", event_cpi)]
 #[derive(Accounts)]
+#[instruction(serialized_args: Vec<u8>, quote_id: u64)]
 pub struct Send<'info> {
     #[account(
         mut,
-        constraint = !state.pause @ CustomError::Paused,
+        constraint = !state.is_paused(PAUSE_SEND) @ CustomError::Paused,
+        constraint = state.version == GlobalState::VERSION @ CustomError::UnsupportedVersion,
         seeds = [GlobalState::SEED_PREFIX], bump
     )]
     pub state: Account<'info, GlobalState>,
@@ -33,6 +36,9 @@ pub struct Send<'info> {
     pub sender_token_account: InterfaceAccount<'info, TokenAccount>,
 
     #[account(
+        constraint = !bridge_token.is_migrated() @ CustomError::TokenMigrated,
+        constraint = !bridge_token.paused @ CustomError::TokenPaused,
+        constraint = bridge_token.version == TokenConfig::VERSION @ CustomError::UnsupportedVersion,
         seeds = [TokenConfig::SEED_PREFIX, mint.key().as_ref()], bump = bridge_token.bump
     )]
     pub bridge_token: Account<'info, TokenConfig>,
@@ -47,6 +53,31 @@ pub struct Send<'info> {
     #[account(mut)]
     pub mint: InterfaceAccount<'info, Mint>,
 
+    // `init` fails if the quote was already used
+    #[account(
+        init,
+        payer = sender,
+        space = ConsumedQuote::ACCOUNT_SIZE,
+        seeds = [ConsumedQuote::SEED_PREFIX, sender.key().as_ref(), quote_id.to_le_bytes().as_ref()], bump
+    )]
+    pub consumed_quote: Account<'info, ConsumedQuote>,
+
+    // optional only until `init_send_accumulator` runs on an existing deployment
+    #[account(
+        mut,
+        seeds = [SendAccumulator::SEED_PREFIX], bump = send_accumulator.bump
+    )]
+    pub send_accumulator: Option<Box<Account<'info, SendAccumulator>>>,
+
+    // Optional receipt of this send, see `SendReceipt`. The sender pays its rent.
+    #[account(
+        init,
+        payer = sender,
+        space = SendReceipt::ACCOUNT_SIZE,
+        seeds = [SendReceipt::SEED_PREFIX, (state.nonce + 1).to_le_bytes().as_ref()], bump
+    )]
+    pub receipt: Option<Box<Account<'info, SendReceipt>>>,
+
     /// CHECK: The address check is needed because otherwise
     /// the supplied Sysvar could be anything else.
     /// The Instruction Sysvar has not been implemented
@@ -57,34 +88,32 @@ pub struct Send<'info> {
     pub system_program: Program<'info, System>,
 }
 
-pub fn send(ctx: Context<Send>, serialized_args: Vec<u8>, recipient: [u8; 20]) -> Result<()> {
+// Permissionless: used quotes can be closed once expired, returning the rent to the sender.
+#[derive(Accounts)]
+pub struct CloseQuote<'info> {
+    #[account(
+        mut,
+        close = sender,
+        has_one = sender,
+        constraint = (Clock::get()?.unix_timestamp as u64) >= consumed_quote.expires_at @ CustomError::QuoteNotExpired,
+    )]
+    pub consumed_quote: Account<'info, ConsumedQuote>,
+
+    /// CHECK: only receives the rent back; checked by `has_one` on `consumed_quote`.
+    #[account(mut)]
+    pub sender: UncheckedAccount<'info>,
+}
+
+// `max_fee` and `deadline` are set by the user, to protect against a quote different from the one shown in the UI
+// and against a transaction landing too late.
+pub fn send(ctx: Context<Send>, serialized_args: Vec<u8>, quote_id: u64, max_fee: u64, deadline: u64) -> Result<()> {
     let args = SendPayload::try_from_slice(&serialized_args)
         .map_err(|_| error!(CustomError::InvalidSerialization))?;
-    let args_hash = hash(&serialized_args);
-
-    // check signature
-    let ix = get_instruction_relative(-1, &ctx.accounts.ix_sysvar.to_account_info())?;
-    require!(ix.program_id == ED25519_ID, CustomError::InvalidSignature);
-    let signed_message = &ix.data[ix.data.len().saturating_sub(32)..];
-    let signer_pubkey = Pubkey::try_from_slice(
-        &ix.data[ix.data.len().saturating_sub(32 + 32)..ix.data.len().saturating_sub(32)],
-    );
+    verify_send_signature(&ctx.accounts.ix_sysvar, ctx.accounts.state.send_signer, &serialized_args)?;
 
-    require!(
-        signed_message == args_hash.to_bytes(),
-        CustomError::InvalidSignature
-    );
-    require!(
-        signer_pubkey? == ctx.accounts.state.send_signer,
-        CustomError::InvalidSignature
-    );
-
-    require!(args.chain_from == SOLANA_CHAIN_ID, CustomError::InvalidArgs);
-    require!(args.chain_to == AMB_CHAIN_ID, CustomError::InvalidArgs);
-    require!(
-        (Clock::get()?.unix_timestamp as u64) < args.timestamp + SIGNATURE_VALIDITY_TIME,
-        CustomError::InvalidArgs
-    );
+    require!(args.sender == ctx.accounts.sender.key(), CustomError::InvalidArgs);
+    require!(args.quote_id == quote_id, CustomError::InvalidArgs);
+    check_quote_terms(args.fee_amount, max_fee, deadline, args.chain_from, args.chain_to, args.timestamp)?;
 
     require!(
         ctx.accounts.mint.key() == args.token_address,
@@ -94,6 +123,13 @@ pub fn send(ctx: Context<Send>, serialized_args: Vec<u8>, recipient: [u8; 20]) -
         ctx.accounts.bridge_token.amb_token == args.token_address_to,
         CustomError::InvalidArgs
     );
+    check_send_flags(&args.flags, &args.flag_data)?;
+
+    ctx.accounts.consumed_quote.set_inner(ConsumedQuote {
+        sender: args.sender,
+        expires_at: args.timestamp + SIGNATURE_VALIDITY_TIME,
+        bump: ctx.bumps.consumed_quote,
+    });
 
     // transfer fee to state PDA
     transfer_native_from_user(
@@ -103,37 +139,22 @@ pub fn send(ctx: Context<Send>, serialized_args: Vec<u8>, recipient: [u8; 20]) -
         ctx.accounts.system_program.to_account_info(),
     )?;
 
-    if ctx.accounts.bridge_token.is_mintable {
-        // burn token
-        burn_spl_from_user(
-            ctx.accounts.sender.to_account_info(),
-            ctx.accounts.sender_token_account.to_account_info(),
-            ctx.accounts.mint.to_account_info(),
-            args.amount_to_send,
-            ctx.accounts.token_program.to_account_info(),
-            ctx.accounts.bridge_token.clone().into_inner(),
-        )?;
-    } else {
-        // transfer token
-        transfer_spl_from_user(
-            ctx.accounts.sender.to_account_info(),
-            ctx.accounts.sender_token_account.to_account_info(),
-            ctx.accounts
-                .bridge_token_account
-                .clone()
-                .expect("no bridge ata")
-                .to_account_info(),
-            args.amount_to_send,
-            ctx.accounts.token_program.to_account_info(),
-        )?;
-    }
+    take_tokens_from_sender(
+        ctx.accounts.sender.to_account_info(),
+        ctx.accounts.sender_token_account.to_account_info(),
+        &ctx.accounts.bridge_token,
+        ctx.accounts.bridge_token_account.as_ref().map(|account| account.to_account_info()),
+        &ctx.accounts.mint,
+        args.amount_to_send,
+        ctx.accounts.token_program.to_account_info(),
+    )?;
 
     // update nonce
     ctx.accounts.state.nonce += 1;
 
-    emit!(SendEvent {
+    let event = SendEvent {
         from: ctx.accounts.sender.key(),
-        to: recipient,
+        to: args.recipient,
         token_address_from: ctx.accounts.mint.key(),
         token_address_to: args.token_address_to,
         amount_from: args.amount_to_send,
@@ -147,6 +168,67 @@ pub fn send(ctx: Context<Send>, serialized_args: Vec<u8>, recipient: [u8; 20]) -
         event_id: ctx.accounts.state.nonce, // transaction number
         flags: args.flags,
         flag_data: args.flag_data,
-    });
+    };
+    accumulate_send(ctx.accounts.send_accumulator.as_mut(), &mut ctx.accounts.state, &event)?;
+    store_send_receipt(ctx.accounts.receipt.as_mut(), ctx.bumps.receipt, ctx.accounts.sender.key(), &event);
+
+    emit_event!(ctx, event.v2(args.fee_amount, args.timestamp, Clock::get()?.slot));
+    Ok(())
+}
+
+// A precompile instruction anywhere in the transaction must hold `send_signer`'s Ed25519 signature over `keccak(serialized_args)`.
+pub fn verify_send_signature(ix_sysvar: &AccountInfo, send_signer: Pubkey, serialized_args: &[u8]) -> Result<()> {
+    let message = hash(serialized_args).to_bytes();
+    find_ed25519_signatures(ix_sysvar, |signatures| {
+        signatures.iter().any(|(signer, signed)| *signer == send_signer && *signed == message)
+    })
+}
+
+pub fn check_quote_terms(fee_amount: u64, max_fee: u64, deadline: u64, chain_from: u64, chain_to: u64, timestamp: u64) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp as u64;
+    require!(fee_amount <= max_fee, CustomError::FeeTooHigh);
+    require!(now <= deadline, CustomError::DeadlineExceeded);
+
+    require!(chain_from == SOLANA_CHAIN_ID, CustomError::InvalidArgs);
+    require!(chain_to == AMB_CHAIN_ID, CustomError::InvalidArgs);
+    require!(now < timestamp + SIGNATURE_VALIDITY_TIME, CustomError::InvalidArgs);
+    Ok(())
+}
+
+// Burns synthetic tokens, or locks the others in the bridge token account.
+pub fn take_tokens_from_sender<'info>(
+    sender: AccountInfo<'info>,
+    sender_token_account: AccountInfo<'info>,
+    bridge_token: &Account<'info, TokenConfig>,
+    bridge_token_account: Option<AccountInfo<'info>>,
+    mint: &InterfaceAccount<'info, Mint>,
+    amount: u64,
+    token_program: AccountInfo<'info>,
+) -> Result<()> {
+    if bridge_token.is_mintable {
+        // burn token
+        burn_spl_from_user(
+            sender,
+            sender_token_account,
+            mint.to_account_info(),
+            amount,
+            token_program,
+            bridge_token.clone().into_inner(),
+        )
+    } else {
+        // transfer token
+        transfer_spl_from_user(
+            sender,
+            sender_token_account,
+            bridge_token_account.expect("no bridge ata"),
+            mint.to_account_info(),
+            mint.decimals,
+            amount,
+            token_program,
+        )
+    }
+}
+
+pub fn close_quote(_ctx: Context<CloseQuote>) -> Result<()> {
     Ok(())
 }
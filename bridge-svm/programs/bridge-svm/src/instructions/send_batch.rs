@@ -0,0 +1,175 @@
+use crate::instructions::{accumulate_send, check_quote_terms, create_send_receipt, take_tokens_from_sender, verify_send_signature};
+use crate::structs::*;
+use crate::utils::events::emit_event;
+use crate::utils::flags::check_send_flags;
+use crate::utils::scale_amount;
+use crate::utils::transfer::transfer_native_from_user;
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar::instructions::ID as SYSVAR_INSTRUCTIONS_ID;
+use anchor_spl::associated_token::get_associated_token_address;
+use anchor_spl::token::Token;
+use anchor_spl::token_interface::Mint;
+
+// Remaining accounts, for each leg in order: mint (mut), bridge_token, sender_token_account (mut),
+// bridge_token_account (mut; bridge program id for mintable tokens), and, if the sender wants receipts,
+// the leg's `SendReceipt` PDA (mut) for all legs.
+pub const ACCOUNTS_PER_LEG: usize = 4;
+pub const ACCOUNTS_PER_LEG_WITH_RECEIPT: usize = ACCOUNTS_PER_LEG + 1;
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+#[instruction(serialized_args: Vec<u8>, quote_id: u64)]
+pub struct SendBatch<'info> {
+    #[account(
+        mut,
+        constraint = !state.is_paused(PAUSE_SEND) @ CustomError::Paused,
+        constraint = state.version == GlobalState::VERSION @ CustomError::UnsupportedVersion,
+        seeds = [GlobalState::SEED_PREFIX], bump
+    )]
+    pub state: Account<'info, GlobalState>,
+
+    #[account(mut)]
+    pub sender: Signer<'info>,
+
+    // `init` fails if the quote was already used
+    #[account(
+        init,
+        payer = sender,
+        space = ConsumedQuote::ACCOUNT_SIZE,
+        seeds = [ConsumedQuote::SEED_PREFIX, sender.key().as_ref(), quote_id.to_le_bytes().as_ref()], bump
+    )]
+    pub consumed_quote: Account<'info, ConsumedQuote>,
+
+    // optional only until `init_send_accumulator` runs on an existing deployment
+    #[account(
+        mut,
+        seeds = [SendAccumulator::SEED_PREFIX], bump = send_accumulator.bump
+    )]
+    pub send_accumulator: Option<Box<Account<'info, SendAccumulator>>>,
+
+    /// CHECK: The address check is needed because otherwise
+    /// the supplied Sysvar could be anything else.
+    #[account(address = SYSVAR_INSTRUCTIONS_ID)]
+    pub ix_sysvar: AccountInfo<'info>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn send_batch<'info>(
+    ctx: Context<'_, '_, 'info, 'info, SendBatch<'info>>,
+    serialized_args: Vec<u8>,
+    quote_id: u64,
+    max_fee: u64,
+    deadline: u64,
+) -> Result<()> {
+    let args = SendBatchPayload::try_from_slice(&serialized_args)
+        .map_err(|_| error!(CustomError::InvalidSerialization))?;
+    verify_send_signature(&ctx.accounts.ix_sysvar, ctx.accounts.state.send_signer, &serialized_args)?;
+
+    require!(args.sender == ctx.accounts.sender.key(), CustomError::InvalidArgs);
+    require!(args.quote_id == quote_id, CustomError::InvalidArgs);
+    check_quote_terms(args.fee_amount, max_fee, deadline, args.chain_from, args.chain_to, args.timestamp)?;
+
+    require!(!args.legs.is_empty() && args.legs.len() <= MAX_BATCH_LEGS, CustomError::InvalidArgs);
+    let accounts_per_leg = ctx.remaining_accounts.len() / args.legs.len();
+    require!(
+        ctx.remaining_accounts.len() == args.legs.len() * accounts_per_leg
+            && (accounts_per_leg == ACCOUNTS_PER_LEG || accounts_per_leg == ACCOUNTS_PER_LEG_WITH_RECEIPT),
+        CustomError::InvalidArgs
+    );
+
+    ctx.accounts.consumed_quote.set_inner(ConsumedQuote {
+        sender: args.sender,
+        expires_at: args.timestamp + SIGNATURE_VALIDITY_TIME,
+        bump: ctx.bumps.consumed_quote,
+    });
+
+    // transfer fee to state PDA, once for the whole batch
+    transfer_native_from_user(
+        ctx.accounts.sender.to_account_info(),
+        ctx.accounts.state.to_account_info(),
+        args.fee_amount,
+        ctx.accounts.system_program.to_account_info(),
+    )?;
+
+    let sender = ctx.accounts.sender.key();
+    let slot = Clock::get()?.slot;
+    for (i, (leg, accounts)) in args.legs.into_iter().zip(ctx.remaining_accounts.chunks_exact(accounts_per_leg)).enumerate() {
+        let (
+            [mint_info, bridge_token_info, sender_token_account_info, bridge_token_account_info],
+            receipt_info,
+        ) = (&accounts[..ACCOUNTS_PER_LEG], accounts.get(ACCOUNTS_PER_LEG)) else {
+            unreachable!()
+        };
+
+        let mint = InterfaceAccount::<Mint>::try_from(mint_info)?;
+        require!(mint.key() == leg.token_address, CustomError::InvalidArgs);
+
+        let bridge_token = Account::<TokenConfig>::try_from(bridge_token_info)?;
+        let bridge_token_pda = Pubkey::create_program_address(
+            &[TokenConfig::SEED_PREFIX, mint.key().as_ref(), &[bridge_token.bump]],
+            ctx.program_id,
+        )
+        .map_err(|_| error!(CustomError::InvalidToken))?;
+        require!(bridge_token.key() == bridge_token_pda, CustomError::InvalidToken);
+        require!(bridge_token.version == TokenConfig::VERSION, CustomError::UnsupportedVersion);
+        require!(!bridge_token.is_migrated(), CustomError::TokenMigrated);
+        require!(!bridge_token.paused, CustomError::TokenPaused);
+        require!(bridge_token.amb_token == leg.token_address_to, CustomError::InvalidArgs);
+        check_send_flags(&leg.flags, &leg.flag_data)?;
+
+        require!(
+            sender_token_account_info.key() == get_associated_token_address(&sender, &mint.key()),
+            CustomError::InvalidArgs
+        );
+        let bridge_token_account = if bridge_token.is_mintable {
+            None
+        } else {
+            require!(
+                bridge_token_account_info.key() == get_associated_token_address(&bridge_token.key(), &mint.key()),
+                CustomError::InvalidArgs
+            );
+            Some(bridge_token_account_info.clone())
+        };
+
+        take_tokens_from_sender(
+            ctx.accounts.sender.to_account_info(),
+            sender_token_account_info.clone(),
+            &bridge_token,
+            bridge_token_account,
+            &mint,
+            leg.amount_to_send,
+            ctx.accounts.token_program.to_account_info(),
+        )?;
+
+        // update nonce, each leg gets its own event id
+        ctx.accounts.state.nonce += 1;
+
+        let event = SendEvent {
+            from: sender,
+            to: args.recipient,
+            token_address_from: mint.key(),
+            token_address_to: leg.token_address_to,
+            amount_from: leg.amount_to_send,
+            amount_to: scale_amount(leg.amount_to_send, mint.decimals, bridge_token.amb_decimals),
+            chain_from: SOLANA_CHAIN_ID,
+            chain_to: AMB_CHAIN_ID,
+            event_id: ctx.accounts.state.nonce,
+            flags: leg.flags,
+            flag_data: leg.flag_data,
+        };
+        accumulate_send(ctx.accounts.send_accumulator.as_mut(), &mut ctx.accounts.state, &event)?;
+        if let Some(receipt_info) = receipt_info {
+            create_send_receipt(
+                receipt_info,
+                ctx.accounts.sender.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+                ctx.program_id,
+                &event,
+            )?;
+        }
+        // one fee for the whole batch, on its first event
+        emit_event!(ctx, event.v2(if i == 0 { args.fee_amount } else { 0 }, args.timestamp, slot));
+    }
+    Ok(())
+}
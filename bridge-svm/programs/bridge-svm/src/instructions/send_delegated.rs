@@ -0,0 +1,157 @@
+use crate::instructions::{accumulate_send, check_quote_terms, store_send_receipt, take_tokens_from_sender, verify_send_signature};
+use crate::structs::*;
+use crate::utils::events::emit_event;
+use crate::utils::flags::check_send_flags;
+use crate::utils::scale_amount;
+use crate::utils::transfer::transfer_native_from_user;
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{program_option::COption, sysvar::instructions::ID as SYSVAR_INSTRUCTIONS_ID};
+use anchor_spl::token::Token;
+use anchor_spl::token_interface::{Mint, TokenAccount};
+
+// Same as `Send`, but the tokens come from any account that approved `sender` as its delegate
+// (PDA-owned vaults, multisigs, non-ATA accounts). The quote is bound to `sender`, who also pays the fee.
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+#[instruction(serialized_args: Vec<u8>, quote_id: u64)]
+pub struct SendDelegated<'info> {
+    #[account(
+        mut,
+        constraint = !state.is_paused(PAUSE_SEND) @ CustomError::Paused,
+        constraint = state.version == GlobalState::VERSION @ CustomError::UnsupportedVersion,
+        seeds = [GlobalState::SEED_PREFIX], bump
+    )]
+    pub state: Account<'info, GlobalState>,
+
+    #[account(mut)]
+    pub sender: Signer<'info>,
+
+    #[account(
+        mut,
+        token::mint = mint,
+        constraint = source_token_account.delegate == COption::Some(sender.key()) @ CustomError::NotDelegate,
+    )]
+    pub source_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        constraint = !bridge_token.is_migrated() @ CustomError::TokenMigrated,
+        constraint = !bridge_token.paused @ CustomError::TokenPaused,
+        constraint = bridge_token.version == TokenConfig::VERSION @ CustomError::UnsupportedVersion,
+        seeds = [TokenConfig::SEED_PREFIX, mint.key().as_ref()], bump = bridge_token.bump
+    )]
+    pub bridge_token: Account<'info, TokenConfig>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = bridge_token,
+    )]
+    pub bridge_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    // `init` fails if the quote was already used
+    #[account(
+        init,
+        payer = sender,
+        space = ConsumedQuote::ACCOUNT_SIZE,
+        seeds = [ConsumedQuote::SEED_PREFIX, sender.key().as_ref(), quote_id.to_le_bytes().as_ref()], bump
+    )]
+    pub consumed_quote: Account<'info, ConsumedQuote>,
+
+    // optional only until `init_send_accumulator` runs on an existing deployment
+    #[account(
+        mut,
+        seeds = [SendAccumulator::SEED_PREFIX], bump = send_accumulator.bump
+    )]
+    pub send_accumulator: Option<Box<Account<'info, SendAccumulator>>>,
+
+    // Optional receipt of this send, see `SendReceipt`. The sender pays its rent.
+    #[account(
+        init,
+        payer = sender,
+        space = SendReceipt::ACCOUNT_SIZE,
+        seeds = [SendReceipt::SEED_PREFIX, (state.nonce + 1).to_le_bytes().as_ref()], bump
+    )]
+    pub receipt: Option<Box<Account<'info, SendReceipt>>>,
+
+    /// CHECK: The address check is needed because otherwise
+    /// the supplied Sysvar could be anything else.
+    #[account(address = SYSVAR_INSTRUCTIONS_ID)]
+    pub ix_sysvar: AccountInfo<'info>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+// `SendEvent.from` is the owner of `source_token_account`, not the delegate.
+pub fn send_delegated(
+    ctx: Context<SendDelegated>,
+    serialized_args: Vec<u8>,
+    quote_id: u64,
+    max_fee: u64,
+    deadline: u64,
+) -> Result<()> {
+    let args = SendPayload::try_from_slice(&serialized_args)
+        .map_err(|_| error!(CustomError::InvalidSerialization))?;
+    verify_send_signature(&ctx.accounts.ix_sysvar, ctx.accounts.state.send_signer, &serialized_args)?;
+
+    require!(args.sender == ctx.accounts.sender.key(), CustomError::InvalidArgs);
+    require!(args.quote_id == quote_id, CustomError::InvalidArgs);
+    check_quote_terms(args.fee_amount, max_fee, deadline, args.chain_from, args.chain_to, args.timestamp)?;
+
+    require!(ctx.accounts.mint.key() == args.token_address, CustomError::InvalidArgs);
+    require!(ctx.accounts.bridge_token.amb_token == args.token_address_to, CustomError::InvalidArgs);
+    check_send_flags(&args.flags, &args.flag_data)?;
+
+    ctx.accounts.consumed_quote.set_inner(ConsumedQuote {
+        sender: args.sender,
+        expires_at: args.timestamp + SIGNATURE_VALIDITY_TIME,
+        bump: ctx.bumps.consumed_quote,
+    });
+
+    // transfer fee to state PDA
+    transfer_native_from_user(
+        ctx.accounts.sender.to_account_info(),
+        ctx.accounts.state.to_account_info(),
+        args.fee_amount,
+        ctx.accounts.system_program.to_account_info(),
+    )?;
+
+    // the token program checks the delegated amount
+    take_tokens_from_sender(
+        ctx.accounts.sender.to_account_info(),
+        ctx.accounts.source_token_account.to_account_info(),
+        &ctx.accounts.bridge_token,
+        ctx.accounts.bridge_token_account.as_ref().map(|account| account.to_account_info()),
+        &ctx.accounts.mint,
+        args.amount_to_send,
+        ctx.accounts.token_program.to_account_info(),
+    )?;
+
+    // update nonce
+    ctx.accounts.state.nonce += 1;
+
+    let event = SendEvent {
+        from: ctx.accounts.source_token_account.owner,
+        to: args.recipient,
+        token_address_from: ctx.accounts.mint.key(),
+        token_address_to: args.token_address_to,
+        amount_from: args.amount_to_send,
+        amount_to: scale_amount(
+            args.amount_to_send,
+            ctx.accounts.mint.decimals,
+            ctx.accounts.bridge_token.amb_decimals
+        ),
+        chain_from: SOLANA_CHAIN_ID,
+        chain_to: AMB_CHAIN_ID,
+        event_id: ctx.accounts.state.nonce,
+        flags: args.flags,
+        flag_data: args.flag_data,
+    };
+    accumulate_send(ctx.accounts.send_accumulator.as_mut(), &mut ctx.accounts.state, &event)?;
+    store_send_receipt(ctx.accounts.receipt.as_mut(), ctx.bumps.receipt, ctx.accounts.sender.key(), &event);
+
+    emit_event!(ctx, event.v2(args.fee_amount, args.timestamp, Clock::get()?.slot));
+    Ok(())
+}
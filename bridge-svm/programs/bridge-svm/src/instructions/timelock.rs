@@ -0,0 +1,234 @@
+use crate::instructions::has_role;
+use crate::structs::*;
+use crate::utils::events::emit_event;
+use crate::utils::transfer::set_mint_authority;
+use anchor_lang::prelude::*;
+use anchor_spl::token::{spl_token::instruction::AuthorityType, Mint, Token};
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+pub struct QueueOperation<'info> {
+    #[account(
+        mut,
+        constraint = state.version == GlobalState::VERSION @ CustomError::UnsupportedVersion,
+        seeds = [GlobalState::SEED_PREFIX], bump
+    )]
+    pub state: Account<'info, GlobalState>,
+
+    #[account(mut)]
+    pub proposer: Signer<'info>,
+
+    #[account(
+        seeds = [RoleAccount::SEED_PREFIX, proposer.key().as_ref()], bump
+    )]
+    pub proposer_roles: Option<Account<'info, RoleAccount>>,
+
+    #[account(
+        init,
+        payer = proposer,
+        space = QueuedOperation::ACCOUNT_SIZE,
+        seeds = [QueuedOperation::SEED_PREFIX, state.timelock_nonce.to_le_bytes().as_ref()], bump
+    )]
+    pub queued_operation: Account<'info, QueuedOperation>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+pub struct CancelOperation<'info> {
+    #[account(
+        constraint = state.admin == canceller.key() || state.is_guardian(canceller.key) @ CustomError::NotGuardian,
+        constraint = state.version == GlobalState::VERSION @ CustomError::UnsupportedVersion,
+        seeds = [GlobalState::SEED_PREFIX], bump
+    )]
+    pub state: Account<'info, GlobalState>,
+
+    pub canceller: Signer<'info>,
+
+    #[account(
+        mut,
+        close = proposer,
+        has_one = proposer,
+    )]
+    pub queued_operation: Account<'info, QueuedOperation>,
+
+    /// CHECK: only receives the rent back; checked by `has_one` on `queued_operation`.
+    #[account(mut)]
+    pub proposer: UncheckedAccount<'info>,
+}
+
+// Permissionless: anyone can execute a queued operation once its delay has passed.
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+pub struct ExecuteOperation<'info> {
+    #[account(
+        mut,
+        constraint = state.version == GlobalState::VERSION @ CustomError::UnsupportedVersion,
+        seeds = [GlobalState::SEED_PREFIX], bump
+    )]
+    pub state: Account<'info, GlobalState>,
+
+    #[account(
+        mut,
+        close = proposer,
+        has_one = proposer,
+    )]
+    pub queued_operation: Account<'info, QueuedOperation>,
+
+    /// CHECK: only receives the rent back; checked by `has_one` on `queued_operation`.
+    #[account(mut)]
+    pub proposer: UncheckedAccount<'info>,
+
+    // Required by `WithdrawFees`, must match the queued receiver.
+    /// CHECK: only receives lamports; checked against the queued operation.
+    #[account(mut)]
+    pub receiver: Option<UncheckedAccount<'info>>,
+
+    // Required by `MigrateToken`.
+    #[account(mut)]
+    pub mint: Option<Account<'info, Mint>>,
+    #[account(mut)]
+    pub bridge_token: Option<Account<'info, TokenConfig>>,
+    pub token_program: Option<Program<'info, Token>>,
+}
+
+pub fn queue_operation(ctx: Context<QueueOperation>, operation: TimelockOperation) -> Result<()> {
+    let state = &mut ctx.accounts.state;
+    let proposer = ctx.accounts.proposer.key();
+
+    let allowed = match operation.required_role() {
+        Some(role) => has_role(state, &proposer, ctx.accounts.proposer_roles.as_deref(), role),
+        None => proposer == state.admin,
+    };
+    require!(allowed, CustomError::MissingRole);
+    if let TimelockOperation::SetTimelockDelay { delay } = operation {
+        check_timelock_delay(delay)?;
+    }
+
+    let id = state.timelock_nonce;
+    let eta = (Clock::get()?.unix_timestamp as u64)
+        .checked_add(state.timelock_delay)
+        .ok_or(CustomError::MathOverflow)?;
+    state.timelock_nonce += 1;
+
+    ctx.accounts.queued_operation.set_inner(QueuedOperation {
+        id,
+        operation: operation.clone(),
+        proposer,
+        eta,
+        bump: ctx.bumps.queued_operation,
+    });
+
+    emit_event!(ctx, OperationQueued {
+        id,
+        operation,
+        proposer,
+        eta,
+    });
+    Ok(())
+}
+
+pub fn cancel_operation(ctx: Context<CancelOperation>) -> Result<()> {
+    emit_event!(ctx, OperationCancelled {
+        id: ctx.accounts.queued_operation.id,
+        sender: ctx.accounts.canceller.key(),
+    });
+    Ok(())
+}
+
+pub fn execute_operation(ctx: Context<ExecuteOperation>) -> Result<()> {
+    let QueuedOperation { id, operation, eta, .. } = ctx.accounts.queued_operation.clone().into_inner();
+    require!(
+        (Clock::get()?.unix_timestamp as u64) >= eta,
+        CustomError::TimelockNotReady
+    );
+
+    match operation {
+        TimelockOperation::SetSigners { send_signer, receive_signer } => {
+            ctx.accounts.state.send_signer = send_signer;
+            // payloads signed by the old receive set can't be received anymore
+            if ctx.accounts.state.receive_signer != receive_signer {
+                ctx.accounts.state.receive_signer = receive_signer;
+                ctx.accounts.state.signer_epoch += 1;
+            }
+        }
+        TimelockOperation::MigrateToken { mint, successor_program } => {
+            let migrated = migrate_token(ctx.accounts, mint, successor_program)?;
+            emit_event!(ctx, migrated);
+        }
+        TimelockOperation::WithdrawFees { receiver, amount } => {
+            require!(!ctx.accounts.state.is_paused(PAUSE_FEE_WITHDRAWAL), CustomError::Paused);
+            let receiver_account = ctx.accounts.receiver.as_ref().ok_or(CustomError::InvalidArgs)?;
+            require!(receiver_account.key() == receiver, CustomError::InvalidArgs);
+
+            let vault = ctx.accounts.state.to_account_info();
+            // Ensure the vault has enough balance
+            require!(
+                **vault.lamports.borrow() >= amount,
+                ErrorCode::RequireGteViolated
+            );
+
+            // Transfer SOL from vault to receiver
+            **vault.try_borrow_mut_lamports()? -= amount;
+            **receiver_account.to_account_info().try_borrow_mut_lamports()? += amount;
+        }
+        TimelockOperation::SetTimelockDelay { delay } => {
+            check_timelock_delay(delay)?;
+            ctx.accounts.state.timelock_delay = delay;
+        }
+    }
+
+    emit_event!(ctx, OperationExecuted { id, operation });
+    Ok(())
+}
+
+// A delay the timelock can't recover from would lock all sensitive operations for good.
+pub fn check_timelock_delay(delay: u64) -> Result<()> {
+    require!(delay <= MAX_TIMELOCK_DELAY, CustomError::TimelockDelayTooLong);
+    Ok(())
+}
+
+// Hands a synthetic token over to a successor bridge: its token PDA (same seeds as ours) becomes the mint
+// and freeze authority, and the token is paused and marked as migrated here.
+// Metadata update authority is held by the admin, not the bridge, so it's transferred off-chain.
+fn migrate_token(accounts: &mut ExecuteOperation, mint: Pubkey, successor_program: Pubkey) -> Result<TokenMigrated> {
+    let mint_account = accounts.mint.as_ref().ok_or(CustomError::InvalidArgs)?;
+    let bridge_token = accounts.bridge_token.as_mut().ok_or(CustomError::InvalidArgs)?;
+    let token_program = accounts.token_program.as_ref().ok_or(CustomError::InvalidArgs)?;
+    require!(mint_account.key() == mint, CustomError::InvalidArgs);
+    require!(bridge_token.token == mint, CustomError::InvalidArgs);
+    require!(bridge_token.version == TokenConfig::VERSION, CustomError::UnsupportedVersion);
+    require!(bridge_token.is_mintable, CustomError::InvalidToken);
+    require!(!bridge_token.is_migrated(), CustomError::TokenMigrated);
+
+    let (new_authority, _) = Pubkey::find_program_address(&[TokenConfig::SEED_PREFIX, mint.as_ref()], &successor_program);
+
+    set_mint_authority(
+        bridge_token.to_account_info(),
+        mint_account.to_account_info(),
+        AuthorityType::MintTokens,
+        new_authority,
+        token_program.to_account_info(),
+        bridge_token.clone().into_inner(),
+    )?;
+    if mint_account.freeze_authority == Some(bridge_token.key()).into() {
+        set_mint_authority(
+            bridge_token.to_account_info(),
+            mint_account.to_account_info(),
+            AuthorityType::FreezeAccount,
+            new_authority,
+            token_program.to_account_info(),
+            bridge_token.clone().into_inner(),
+        )?;
+    }
+
+    bridge_token.paused = true;
+    bridge_token.migrated_to = successor_program;
+
+    Ok(TokenMigrated {
+        mint,
+        successor_program,
+        new_authority,
+    })
+}
@@ -1,3 +1,6 @@
+// The generated CPI helpers take one argument per instruction argument.
+#![allow(clippy::too_many_arguments)]
+
 use anchor_lang::prelude::*;
 
 pub mod instructions;
@@ -5,6 +8,7 @@ pub mod structs;
 pub mod utils;
 
 use crate::instructions::*;
+use crate::structs::{ReceiveLeg, Role, SourceRef, TimelockOperation};
 
 declare_id!("ambZMSUBvU8bLfxop5uupQd9tcafeJKea1KoyTv2yM1");
 
@@ -16,8 +20,9 @@ pub mod amb_sol_bridge {
         ctx: Context<Initialize>,
         send_signer: Pubkey,
         receive_signer: Pubkey,
+        timelock_delay: u64,
     ) -> Result<()> {
-        instructions::initialize(ctx, send_signer, receive_signer)
+        instructions::initialize(ctx, send_signer, receive_signer, timelock_delay)
     }
 
     pub fn initialize_token(
@@ -29,35 +34,178 @@ pub mod amb_sol_bridge {
         instructions::initialize_token(ctx, amb_token, amb_decimals, is_mintable)
     }
 
-    pub fn set_pause(ctx: Context<UpdateState>, pause: bool) -> Result<()> {
-        instructions::set_pause(ctx, pause)
+    pub fn set_pause(ctx: Context<UpdateState>, flags: u8, pause: bool, reason: u16) -> Result<()> {
+        instructions::set_pause(ctx, flags, pause, reason)
+    }
+
+    pub fn set_token_pause(ctx: Context<UpdateToken>, pause: bool) -> Result<()> {
+        instructions::set_token_pause(ctx, pause)
+    }
+
+    pub fn emergency_pause(ctx: Context<EmergencyPause>, reason: u16) -> Result<()> {
+        instructions::emergency_pause(ctx, reason)
+    }
+
+    pub fn set_guardians(ctx: Context<UpdateState>, guardians: Vec<Pubkey>) -> Result<()> {
+        instructions::set_guardians(ctx, guardians)
+    }
+
+    pub fn set_native_send_amount(ctx: Context<UpdateState>, amount: u64) -> Result<()> {
+        instructions::set_native_send_amount(ctx, amount)
+    }
+
+    pub fn grant_role(ctx: Context<ManageRole>, member: Pubkey, role: Role) -> Result<()> {
+        instructions::grant_role(ctx, member, role)
+    }
+
+    pub fn revoke_role(ctx: Context<RevokeRole>, member: Pubkey, role: Role) -> Result<()> {
+        instructions::revoke_role(ctx, member, role)
+    }
+
+    pub fn queue_operation(ctx: Context<QueueOperation>, operation: TimelockOperation) -> Result<()> {
+        instructions::queue_operation(ctx, operation)
+    }
+
+    pub fn cancel_operation(ctx: Context<CancelOperation>) -> Result<()> {
+        instructions::cancel_operation(ctx)
+    }
+
+    pub fn execute_operation(ctx: Context<ExecuteOperation>) -> Result<()> {
+        instructions::execute_operation(ctx)
+    }
+
+    pub fn migrate_state(ctx: Context<MigrateState>, timelock_delay: u64) -> Result<()> {
+        instructions::migrate_state(ctx, timelock_delay)
     }
-    pub fn set_signers(ctx: Context<UpdateState>, send_signer: Pubkey, receive_signer: Pubkey) -> Result<()> {
-        instructions::set_signers(ctx, send_signer, receive_signer)
+
+    pub fn migrate_token_config(ctx: Context<MigrateAccount>) -> Result<()> {
+        instructions::migrate_token_config(ctx)
     }
 
-    pub fn withdraw_fees(ctx: Context<UpdateState>, amount: u64) -> Result<()> {
-        instructions::withdraw(ctx, amount)
+    pub fn migrate_nonce(ctx: Context<MigrateAccount>) -> Result<()> {
+        instructions::migrate_nonce(ctx)
     }
 
 
 
 
-    pub fn send(ctx: Context<Send>, serialized_args: Vec<u8>, recipient: [u8; 20]) -> Result<()> {
-        instructions::send(ctx, serialized_args, recipient)
+    pub fn send(
+        ctx: Context<Send>,
+        serialized_args: Vec<u8>,
+        quote_id: u64,
+        max_fee: u64,
+        deadline: u64,
+    ) -> Result<()> {
+        instructions::send(ctx, serialized_args, quote_id, max_fee, deadline)
+    }
+
+    pub fn send_batch<'info>(
+        ctx: Context<'_, '_, 'info, 'info, SendBatch<'info>>,
+        serialized_args: Vec<u8>,
+        quote_id: u64,
+        max_fee: u64,
+        deadline: u64,
+    ) -> Result<()> {
+        instructions::send_batch(ctx, serialized_args, quote_id, max_fee, deadline)
+    }
+
+    pub fn send_delegated(
+        ctx: Context<SendDelegated>,
+        serialized_args: Vec<u8>,
+        quote_id: u64,
+        max_fee: u64,
+        deadline: u64,
+    ) -> Result<()> {
+        instructions::send_delegated(ctx, serialized_args, quote_id, max_fee, deadline)
+    }
+
+    pub fn close_quote(ctx: Context<CloseQuote>) -> Result<()> {
+        instructions::close_quote(ctx)
+    }
+
+    pub fn set_call_target(ctx: Context<SetCallTarget>, program_id: Pubkey, allowed: bool) -> Result<()> {
+        instructions::set_call_target(ctx, program_id, allowed)
+    }
+
+    pub fn receive_and_call<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ReceiveAndCall<'info>>,
+        amount_to: u64,
+        event_id: u64,
+        expires_at: u64,
+        source: SourceRef,
+        flags: [u8; 32],
+        flag_data: Vec<u8>,
+        skip_call: bool,
+    ) -> Result<()> {
+        instructions::receive_and_call(ctx, amount_to, event_id, expires_at, source, flags, flag_data, skip_call)
+    }
+
+    pub fn send_message(
+        ctx: Context<SendMessage>,
+        serialized_args: Vec<u8>,
+        quote_id: u64,
+        max_fee: u64,
+        deadline: u64,
+    ) -> Result<()> {
+        instructions::send_message(ctx, serialized_args, quote_id, max_fee, deadline)
+    }
+
+    pub fn receive_message(
+        ctx: Context<ReceiveMessage>,
+        sender: [u8; 20],
+        target: Pubkey,
+        event_id: u64,
+        expires_at: u64,
+        payload: Vec<u8>,
+    ) -> Result<()> {
+        instructions::receive_message(ctx, sender, target, event_id, expires_at, payload)
     }
 
     pub fn receive(
         ctx: Context<Receive>,
         amount_to: u64,
         event_id: u64,
+        expires_at: u64,
+        source: SourceRef,
         flags: [u8; 32],
         flag_data: Vec<u8>,
     ) -> Result<()> {
-        instructions::receive(ctx, amount_to, event_id, flags, flag_data)
+        instructions::receive(ctx, amount_to, event_id, expires_at, source, flags, flag_data)
+    }
+
+    pub fn receive_batch<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ReceiveBatch<'info>>,
+        legs: Vec<ReceiveLeg>,
+    ) -> Result<()> {
+        instructions::receive_batch(ctx, legs)
+    }
+
+    pub fn receive_to(
+        ctx: Context<ReceiveTo>,
+        to: Pubkey,
+        amount_to: u64,
+        event_id: u64,
+        expires_at: u64,
+        source: SourceRef,
+        flags: [u8; 32],
+        flag_data: Vec<u8>,
+    ) -> Result<()> {
+        instructions::receive_to(ctx, to, amount_to, event_id, expires_at, source, flags, flag_data)
+    }
+
+    pub fn refund(ctx: Context<Refund>, event_id: u64, amount: u64) -> Result<()> {
+        instructions::refund(ctx, event_id, amount)
+    }
+
+    pub fn skip_nonce(ctx: Context<SkipNonce>, receiver: Pubkey, new_nonce: u64, voided_event_ids: Vec<u64>) -> Result<()> {
+        instructions::skip_nonce(ctx, receiver, new_nonce, voided_event_ids)
+    }
+
+    pub fn init_send_accumulator(ctx: Context<InitSendAccumulator>) -> Result<()> {
+        instructions::init_send_accumulator(ctx)
     }
 
-    pub fn change_mint_authority(ctx: Context<ChangeMintAuthority>, new_authority: Pubkey) -> Result<()> {
-        instructions::change_mint_authority(ctx, new_authority)
+    pub fn close_send_receipt(ctx: Context<CloseSendReceipt>, event_id: u64) -> Result<()> {
+        instructions::close_send_receipt(ctx, event_id)
     }
 }
@@ -7,6 +7,23 @@ pub const SIGNATURE_VALIDITY_TIME: u64 = 30 * 60; // 30 minutes
 
 pub const ZERO_PUBKEY: Pubkey = Pubkey::new_from_array([0u8; 32]);
 
+pub const MAX_GUARDIANS: usize = 8;
+
+pub const MAX_BATCH_LEGS: usize = 8;
+
+pub const MAX_SKIPPED_NONCES: usize = 32;
+
+pub const MAX_MESSAGE_PAYLOAD: usize = 512;  // same as `MAX_FLAG_DATA_LEN`
+
+pub const MAX_TIMELOCK_DELAY: u64 = 30 * 24 * 60 * 60; // 30 days
+
+// `GlobalState.pause_flags` bits
+pub const PAUSE_SEND: u8 = 1 << 0;
+pub const PAUSE_RECEIVE: u8 = 1 << 1;
+pub const PAUSE_TOKEN_REGISTRATION: u8 = 1 << 2;
+pub const PAUSE_FEE_WITHDRAWAL: u8 = 1 << 3;
+pub const PAUSE_ALL: u8 = PAUSE_SEND | PAUSE_RECEIVE | PAUSE_TOKEN_REGISTRATION | PAUSE_FEE_WITHDRAWAL;
+
 
 #[cfg(feature = "mainnet")]
 pub const SOLANA_CHAIN_ID: u64 = 0x534f4c414e410000; // "SOLANA" in hex
@@ -22,46 +39,258 @@ pub const AMB_CHAIN_ID: u64 = 22040;
 
 #[account]
 pub struct GlobalState {
+    pub version: u8,              // layout version, see `migrate_state`
     pub admin: Pubkey,
     pub send_signer: Pubkey,
     pub receive_signer: Pubkey,
     pub nonce: u64,
-    pub pause: bool,
+    pub pause_flags: u8,          // bitmask of paused paths, see `PAUSE_*`
+    pub pause_reason: u16,        // reason code of the last pause change
+    pub guardians: Vec<Pubkey>,   // can pause the bridge and cancel queued timelock operations
+    pub timelock_delay: u64,      // seconds between queueing and executing a sensitive operation
+    pub timelock_nonce: u64,      // id of the next queued operation
+    pub native_send_amount: u64,  // lamports dropped off to receivers that set `SEND_NATIVE_TO_RECEIVER`
+    pub signer_epoch: u64,        // bumped on every signer change; receive payloads of earlier epochs are rejected
+    pub send_root: [u8; 32],      // root of the `SendAccumulator` tree, zero until it's initialized
+    pub reserved: [u8; 16],       // space for new fields without realloc
 }
 
 impl GlobalState {
     pub const SEED_PREFIX: &'static [u8] = b"global_state";
-    pub const ACCOUNT_SIZE: usize = 8 + 32 + 32 + 32 + 8 + 1;     // discriminator (8) + admin (32) + send_signer (32) + receive_signer (32) + nonce (8) + pause (1)
+    pub const VERSION: u8 = 1;
+    pub const ACCOUNT_SIZE: usize = 8 + 1 + 32 + 32 + 32 + 8 + 1 + 2 // discriminator (8) + version (1) + admin (32) + send_signer (32) + receive_signer (32) + nonce (8) + pause_flags (1) + pause_reason (2)
+        + 4 + 32 * MAX_GUARDIANS + 8 + 8 + 8 + 8 + 32 + 16;             // guardians (4 + 32 * MAX_GUARDIANS) + timelock_delay (8) + timelock_nonce (8) + native_send_amount (8) + signer_epoch (8) + send_root (32) + reserved (16)
+    pub const LEGACY_ACCOUNT_SIZE: usize = 8 + 32 + 32 + 32 + 8 + 1;    // unversioned layout: discriminator (8) + admin (32) + send_signer (32) + receive_signer (32) + nonce (8) + pause (1)
+
+    pub fn is_paused(&self, flag: u8) -> bool {
+        self.pause_flags & flag != 0
+    }
+
+    pub fn is_guardian(&self, key: &Pubkey) -> bool {
+        self.guardians.contains(key)
+    }
 }
 
 
 #[account]
 pub struct TokenConfig {
+    pub version: u8,        // layout version, see `migrate_token_config`
     pub token: Pubkey,      // Public key of the token
     pub amb_token: [u8; 20],  // Address of the token on the AMB bridge
     pub amb_decimals: u8,       // Decimals of the token on the AMB bridge
     pub is_mintable: bool,    // True for synthetic tokens like SAMB, that come from AMB network and are minted on Solana by the bridge
     pub bump: u8,
+    pub paused: bool,       // True if sends and receives of this token are paused
+    pub migrated_to: Pubkey,  // Successor bridge program that took over the mint, `ZERO_PUBKEY` if not migrated
+    pub reserved: [u8; 32],   // space for new fields without realloc
 }
 
 impl TokenConfig {
     pub const SEED_PREFIX: &'static[u8] = b"token";
-    pub const ACCOUNT_SIZE: usize = 8 + 32 + 20 + 1 + 1 + 1;
+    pub const VERSION: u8 = 1;
+    pub const ACCOUNT_SIZE: usize = 8 + 1 + 32 + 20 + 1 + 1 + 1 + 1 + 32 + 32;   // discriminator (8) + version (1) + token (32) + amb_token (20) + amb_decimals (1) + is_mintable (1) + bump (1) + paused (1) + migrated_to (32) + reserved (32)
+    pub const LEGACY_ACCOUNT_SIZE: usize = 8 + 32 + 20 + 1 + 1 + 1;
+
+    pub fn new(token: Pubkey, amb_token: [u8; 20], amb_decimals: u8, is_mintable: bool, bump: u8, paused: bool) -> Self {
+        Self { version: Self::VERSION, token, amb_token, amb_decimals, is_mintable, bump, paused, migrated_to: ZERO_PUBKEY, reserved: [0; 32] }
+    }
 
-    pub fn new(token: Pubkey, amb_token: [u8; 20], amb_decimals: u8, is_mintable: bool, bump: u8) -> Self {
-        Self { token, amb_token, amb_decimals, is_mintable, bump }
+    pub fn is_migrated(&self) -> bool {
+        self.migrated_to != ZERO_PUBKEY
     }
 }
 
 
 #[account]
 pub struct NonceAccount {
+    pub version: u8,
     pub nonce_counter: u64,
+    pub reserved: [u8; 16],
 }
 
 impl NonceAccount {
     pub const SEED_PREFIX: &'static [u8] = b"nonce";
-    pub const ACCOUNT_SIZE: usize = 8 + 8;     // discriminator (8) + nonce (8)
+    pub const VERSION: u8 = 1;
+    pub const ACCOUNT_SIZE: usize = 8 + 1 + 8 + 16;     // discriminator (8) + version (1) + nonce (8) + reserved (16)
+    pub const LEGACY_ACCOUNT_SIZE: usize = 8 + 8;       // unversioned layout: discriminator (8) + nonce (8)
+}
+
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Role {
+    Pauser,         // can pause and unpause the bridge
+    TokenManager,   // can register tokens
+    SignerManager,  // can change send and receive signers
+    FeeCollector,   // can withdraw collected fees
+}
+
+impl Role {
+    pub fn mask(self) -> u8 {
+        1 << self as u8
+    }
+}
+
+
+// Roles granted to a single member. The super-admin is `GlobalState.admin`; it implicitly holds every role
+// and is the only one who can grant or revoke them.
+#[account]
+pub struct RoleAccount {
+    pub member: Pubkey,
+    pub roles: u8,    // bitmask of `Role::mask()`
+    pub bump: u8,
+}
+
+impl RoleAccount {
+    pub const SEED_PREFIX: &'static [u8] = b"roles";
+    pub const ACCOUNT_SIZE: usize = 8 + 32 + 1 + 1;     // discriminator (8) + member (32) + roles (1) + bump (1)
+
+    pub fn has_role(&self, role: Role) -> bool {
+        self.roles & role.mask() != 0
+    }
+}
+
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug)]
+pub enum TimelockOperation {
+    SetSigners { send_signer: Pubkey, receive_signer: Pubkey },
+    MigrateToken { mint: Pubkey, successor_program: Pubkey },
+    WithdrawFees { receiver: Pubkey, amount: u64 },
+    SetTimelockDelay { delay: u64 },
+}
+
+impl TimelockOperation {
+    pub const MAX_SIZE: usize = 1 + 32 + 32;   // variant (1) + largest variant fields (64)
+
+    // Role allowed to queue the operation; `None` means super-admin only.
+    pub fn required_role(&self) -> Option<Role> {
+        match self {
+            TimelockOperation::SetSigners { .. } => Some(Role::SignerManager),
+            TimelockOperation::WithdrawFees { .. } => Some(Role::FeeCollector),
+            TimelockOperation::MigrateToken { .. } | TimelockOperation::SetTimelockDelay { .. } => None,
+        }
+    }
+}
+
+
+#[account]
+pub struct QueuedOperation {
+    pub id: u64,
+    pub operation: TimelockOperation,
+    pub proposer: Pubkey,   // receives the rent back when the operation is executed or cancelled
+    pub eta: u64,           // earliest execution timestamp
+    pub bump: u8,
+}
+
+impl QueuedOperation {
+    pub const SEED_PREFIX: &'static [u8] = b"timelock";
+    pub const ACCOUNT_SIZE: usize = 8 + 8 + TimelockOperation::MAX_SIZE + 32 + 8 + 1;
+}
+
+
+// Marks a send quote as used. Only needed while the quote is valid, after that anyone can close it.
+#[account]
+pub struct ConsumedQuote {
+    pub sender: Pubkey,     // receives the rent back on close
+    pub expires_at: u64,
+    pub bump: u8,
+}
+
+impl ConsumedQuote {
+    pub const SEED_PREFIX: &'static [u8] = b"quote";
+    pub const ACCOUNT_SIZE: usize = 8 + 32 + 8 + 1;     // discriminator (8) + sender (32) + expires_at (8) + bump (1)
+}
+
+
+// Optional on-chain record of a send, so it can be proven without archival RPC.
+// Anyone can close it once the receive signers confirm the claim on AMB, see `close_send_receipt`.
+// Only token sends get one: AMB has no receipt hash for messages to confirm their claim against.
+#[account]
+pub struct SendReceipt {
+    pub sender: Pubkey,     // receives the rent back on close
+    pub event: SendEvent,
+    pub hash: [u8; 32],     // EVM `ReceiptUtils.toHash` of the receipt, see `send_receipt_hash`
+    pub bump: u8,
+}
+
+impl SendReceipt {
+    pub const SEED_PREFIX: &'static [u8] = b"receipt";
+    pub const ACCOUNT_SIZE: usize = 8 + 32 + SendEvent::MAX_SIZE + 32 + 1;     // discriminator (8) + sender (32) + event + hash (32) + bump (1)
+
+    pub fn new(sender: Pubkey, event: &SendEvent, bump: u8) -> Self {
+        Self {
+            sender,
+            event: event.clone(),
+            hash: crate::utils::receipt::send_receipt_hash(event),
+            bump,
+        }
+    }
+}
+
+
+// Incremental Merkle tree of all sends since `first_event_id`, leaves are `send_receipt_hash` of their `SendEvent`
+// (`message_leaf_hash` of their `MessageSent` for messages) in event id order.
+// Lets AMB verify sends by inclusion proofs against `GlobalState.send_root`, see `utils::merkle`.
+#[account]
+pub struct SendAccumulator {
+    pub first_event_id: u64,  // event id of leaf 0
+    pub count: u64,           // number of leaves
+    pub branch: [[u8; 32]; crate::utils::merkle::TREE_DEPTH],  // frontier of the tree
+    pub bump: u8,
+}
+
+impl SendAccumulator {
+    pub const SEED_PREFIX: &'static [u8] = b"send_accumulator";
+    pub const ACCOUNT_SIZE: usize = 8 + 8 + 8 + 32 * crate::utils::merkle::TREE_DEPTH + 1;     // discriminator (8) + first_event_id (8) + count (8) + branch (32 * TREE_DEPTH) + bump (1)
+}
+
+
+// Program allowed as a bridge-and-call target, see `receive_and_call`.
+#[account]
+pub struct CallTarget {
+    pub program_id: Pubkey,
+    pub allowed: bool,
+    pub bump: u8,
+}
+
+impl CallTarget {
+    pub const SEED_PREFIX: &'static [u8] = b"call_target";
+    pub const ACCOUNT_SIZE: usize = 8 + 32 + 1 + 1;     // discriminator (8) + program_id (32) + allowed (1) + bump (1)
+}
+
+// Signs the CPI into call targets and owns the escrow token accounts they take the received tokens from.
+// One per receiver (`[CALL_AUTHORITY_SEED, receiver]`): targets may record positions under it, and those
+// must only be reachable by calls of the same receiver.
+pub const CALL_AUTHORITY_SEED: &[u8] = b"call_authority";
+
+
+// Marks an inbound message as delivered. Target apps can read it to check what was received.
+#[account]
+pub struct ReceivedMessage {
+    pub event_id: u64,
+    pub sender: [u8; 20],
+    pub target: Pubkey,
+    pub payload_hash: [u8; 32],     // keccak of the payload
+    pub bump: u8,
+}
+
+impl ReceivedMessage {
+    pub const SEED_PREFIX: &'static [u8] = b"message";
+    pub const ACCOUNT_SIZE: usize = 8 + 8 + 20 + 32 + 32 + 1;    // discriminator (8) + event_id (8) + sender (20) + target (32) + payload_hash (32) + bump (1)
+}
+
+// Marks a send as refunded, so it can't be refunded twice. Validators don't claim a refunded send on AMB.
+#[account]
+pub struct RefundedSend {
+    pub event_id: u64,    // `SendEvent.event_id` of the refunded send
+    pub sender: Pubkey,
+    pub token: Pubkey,
+    pub amount: u64,
+    pub bump: u8,
+}
+
+impl RefundedSend {
+    pub const SEED_PREFIX: &'static [u8] = b"refund";
+    pub const ACCOUNT_SIZE: usize = 8 + 8 + 32 + 32 + 8 + 1;    // discriminator (8) + event_id (8) + sender (32) + token (32) + amount (8) + bump (1)
 }
 
 
@@ -81,11 +310,59 @@ pub enum CustomError {
     Paused,
     #[msg("Not an admin")]
     NotAdmin,
+    #[msg("Missing required role")]
+    MissingRole,
+    #[msg("Not a guardian")]
+    NotGuardian,
+    #[msg("Timelock delay has not passed")]
+    TimelockNotReady,
+    #[msg("Timelock delay too long")]
+    TimelockDelayTooLong,
+    #[msg("Arithmetic overflow")]
+    MathOverflow,
+    #[msg("Token is paused")]
+    TokenPaused,
+    #[msg("Unsupported account version")]
+    UnsupportedVersion,
+    #[msg("Account is already migrated")]
+    AlreadyMigrated,
+    #[msg("Not the program upgrade authority")]
+    NotUpgradeAuthority,
+    #[msg("Token is migrated to another bridge")]
+    TokenMigrated,
+    #[msg("Quote has not expired yet")]
+    QuoteNotExpired,
+    #[msg("Fee exceeds max fee")]
+    FeeTooHigh,
+    #[msg("Deadline exceeded")]
+    DeadlineExceeded,
+    #[msg("Not enough lamports in the bridge vault")]
+    VaultInsufficient,
+    #[msg("Unknown or unsupported flags")]
+    UnsupportedFlags,
+    #[msg("Malformed flag data")]
+    InvalidFlagData,
+    #[msg("Flag data too long")]
+    FlagDataTooLong,
+    #[msg("Signer is not the delegate of the token account")]
+    NotDelegate,
+    #[msg("Receive payload expired")]
+    PayloadExpired,
+    #[msg("Call escrow is not empty")]
+    EscrowNotEmpty,
+    #[msg("Send accumulator is full")]
+    AccumulatorFull,
+    #[msg("Message payload too long")]
+    MessageTooLong,
+    #[msg("Send accumulator is required")]
+    AccumulatorRequired,
 }
 
 
 #[derive(BorshSerialize, BorshDeserialize, Debug)]
 pub struct SendPayload {
+    pub sender: Pubkey,  // only this sender can use the quote
+    pub recipient: [u8; 20],
     pub token_address: Pubkey,
     pub token_address_to: [u8; 20],
     pub amount_to_send: u64,
@@ -93,10 +370,153 @@ pub struct SendPayload {
     pub chain_from: u64,  // must be solana
     pub chain_to: u64,  // must be amb
     pub timestamp: u64,
+    pub quote_id: u64,  // unique per sender, each quote can be used once
     pub flags: [u8; 32],
     pub flag_data: Vec<u8>,
 }
 
+// One quote for several tokens sent in one `send_batch`; the fee is paid once for the whole batch.
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct SendBatchPayload {
+    pub sender: Pubkey,
+    pub recipient: [u8; 20],
+    pub legs: Vec<SendLeg>,
+    pub fee_amount: u64,
+    pub chain_from: u64,  // must be solana
+    pub chain_to: u64,  // must be amb
+    pub timestamp: u64,
+    pub quote_id: u64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct SendLeg {
+    pub token_address: Pubkey,
+    pub token_address_to: [u8; 20],
+    pub amount_to_send: u64,
+    pub flags: [u8; 32],
+    pub flag_data: Vec<u8>,
+}
+
+// One transfer of a `receive_batch`; the rest of its `ReceivePayload` comes from the accounts.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct ReceiveLeg {
+    pub amount_to: u64,
+    pub event_id: u64,
+    pub expires_at: u64,
+    pub source: SourceRef,
+    pub flags: [u8; 32],
+    pub flag_data: Vec<u8>,  // starts with the receiver nonce, consecutive across the legs
+}
+
+// Receive signers sign `keccak(TAG || borsh(payload))`. Each payload type has its own tag,
+// so a signature over one type can't be passed off as another that serializes to the same bytes.
+pub trait ReceiveSigned: AnchorSerialize {
+    const TAG: [u8; 8];
+
+    fn signed_message(&self) -> Result<Vec<u8>> {
+        let mut message = Self::TAG.to_vec();
+        self.serialize(&mut message).map_err(|_| CustomError::InvalidSerialization)?;
+        Ok(message)
+    }
+}
+
+impl ReceiveSigned for ReceivePayload {
+    const TAG: [u8; 8] = *b"amb:recv";
+}
+
+impl ReceiveSigned for ReceiveMessagePayload {
+    const TAG: [u8; 8] = *b"amb:rmsg";
+}
+
+impl ReceiveSigned for RefundPayload {
+    const TAG: [u8; 8] = *b"amb:rfnd";
+}
+
+impl ReceiveSigned for ReceiptClaimedPayload {
+    const TAG: [u8; 8] = *b"amb:rclm";
+}
+
+impl ReceiveSigned for SkipNoncePayload {
+    const TAG: [u8; 8] = *b"amb:skip";
+}
+
+// Prefix of the `receive_batch` message, followed by the hashes of the legs' signed messages.
+pub const RECEIVE_BATCH_TAG: [u8; 8] = *b"amb:rbat";
+
+// Call after receive, the `TAG_CALL` entry of `ReceivePayload.flag_data`. The received amount (u64, little-endian)
+// is appended to `data`.
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct CallData {
+    pub program_id: Pubkey,
+    pub data: Vec<u8>,
+}
+
+// Quote for an outbound message, signed by `send_signer` like `SendPayload`.
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct SendMessagePayload {
+    pub sender: Pubkey,
+    pub target: [u8; 20],  // contract on AMB
+    pub payload: Vec<u8>,
+    pub fee_amount: u64,
+    pub chain_from: u64,  // must be solana
+    pub chain_to: u64,  // must be amb
+    pub timestamp: u64,
+    pub quote_id: u64,
+}
+
+// Inbound message, signed by the receive signers like `ReceivePayload`.
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct ReceiveMessagePayload {
+    pub sender: [u8; 20],  // contract on AMB
+    pub target: Pubkey,
+    pub chain_from: u64,  // must be amb
+    pub chain_to: u64,  // must be solana
+    pub event_id: u64,  // nonce of the message, each one can be received once
+    pub expires_at: u64,  // unix timestamp, can't be received after it
+    pub signer_epoch: u64,  // `GlobalState.signer_epoch` the payload was signed under
+    pub payload: Vec<u8>,
+}
+
+// Validators' attestation that a send can't be claimed on AMB, signed by the receive signers.
+// Never signed for a send already claimed on AMB; once refunded, the send's AMB claim is not signed either.
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct RefundPayload {
+    pub event_id: u64,  // `SendEvent.event_id` of the send
+    pub sender: Pubkey,  // `SendEvent.from`, gets the tokens back
+    pub token_address: Pubkey,
+    pub amount: u64,  // `SendEvent.amount_from`
+    pub chain_from: u64,  // of the send, must be solana
+    pub chain_to: u64,  // of the send, must be amb
+}
+
+// Validators' confirmation that the AMB side claimed a send, signed by the receive signers.
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct ReceiptClaimedPayload {
+    pub event_id: u64,
+    pub receipt_hash: [u8; 32],  // `SendReceipt.hash`
+    pub chain_from: u64,  // of the send, must be solana
+    pub chain_to: u64,  // of the send, must be amb
+}
+
+// Validators' decision to void receives that can't be claimed anymore, signed by the receive signers.
+// Committing to `current_nonce` makes it usable once.
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct SkipNoncePayload {
+    pub receiver: Pubkey,
+    pub current_nonce: u64,
+    pub new_nonce: u64,
+    pub voided_event_ids: Vec<u64>,  // one per skipped nonce, in order
+    pub chain_from: u64,  // must be amb
+    pub chain_to: u64,  // must be solana
+}
+
+// The AMB transfer a receive comes from, signed as part of its `ReceivePayload`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, Debug)]
+pub struct SourceRef {
+    pub receipt_hash: [u8; 32],  // `ReceiptUtils.toHash` of the AMB receipt
+    pub tx_hash: [u8; 32],  // AMB transaction that emitted the receipt
+}
+
 #[event]
 // also ReceiveEvent
 pub struct ReceivePayload {
@@ -106,11 +526,15 @@ pub struct ReceivePayload {
     pub chain_from: u64,  // must be amb
     pub chain_to: u64,  // must be solana
     pub event_id: u64,
+    pub expires_at: u64,  // unix timestamp, can't be received after it
+    pub signer_epoch: u64,  // `GlobalState.signer_epoch` the payload was signed under
+    pub source: SourceRef,
     pub flags: [u8; 32],
     pub flag_data: Vec<u8>,
 }
 
 #[event]
+#[derive(Clone)]
 pub struct SendEvent {
     pub from: Pubkey,  // source address (bytes32 because of cross-chain compatibility)
     pub to: [u8; 20],  // destination address (bytes32 because of cross-chain compatibility)
@@ -124,3 +548,234 @@ pub struct SendEvent {
     pub flags: [u8; 32],
     pub flag_data: Vec<u8>,
 }
+
+impl SendEvent {
+    // from (32) + to (20) + token_address_from (32) + token_address_to (20) + amount_from (8) + amount_to (32)
+    // + chain_from (8) + chain_to (8) + event_id (8) + flags (32) + flag_data (4 + MAX_FLAG_DATA_LEN)
+    pub const MAX_SIZE: usize = 32 + 20 + 32 + 20 + 8 + 32 + 8 + 8 + 8 + 32 + 4 + crate::utils::flags::MAX_FLAG_DATA_LEN;
+
+    pub fn v2(self, fee_amount: u64, quote_timestamp: u64, slot: u64) -> SendEventV2 {
+        SendEventV2 {
+            schema_version: EVENT_SCHEMA_VERSION,
+            from: self.from,
+            to: self.to,
+            token_address_from: self.token_address_from,
+            token_address_to: self.token_address_to,
+            amount_from: self.amount_from,
+            amount_to: self.amount_to,
+            chain_from: self.chain_from,
+            chain_to: self.chain_to,
+            event_id: self.event_id,
+            flags: self.flags,
+            flag_data: self.flag_data,
+            fee_amount,
+            fee_token: anchor_spl::token::spl_token::native_mint::ID,
+            quote_timestamp,
+            slot,
+        }
+    }
+}
+
+// Current layout of the `*V2` events; indexers branch on it, new fields only get appended.
+pub const EVENT_SCHEMA_VERSION: u8 = 2;
+
+// `SendEvent` with the quote's terms, emitted instead of it.
+#[event]
+pub struct SendEventV2 {
+    pub schema_version: u8,  // `EVENT_SCHEMA_VERSION`
+    pub from: Pubkey,
+    pub to: [u8; 20],
+    pub token_address_from: Pubkey,
+    pub token_address_to: [u8; 20],
+    pub amount_from: u64,
+    pub amount_to: [u8; 32],
+    pub chain_from: u64,
+    pub chain_to: u64,
+    pub event_id: u64,
+    pub flags: [u8; 32],
+    pub flag_data: Vec<u8>,
+    pub fee_amount: u64,
+    pub fee_token: Pubkey,  // fees are paid in lamports, so the native mint
+    pub quote_timestamp: u64,  // `SendPayload.timestamp`
+    pub slot: u64,
+}
+
+// `ReceivePayload` with references to the source transfer, emitted instead of it.
+#[event]
+pub struct ReceiveEventV2 {
+    pub schema_version: u8,  // `EVENT_SCHEMA_VERSION`
+    pub to: Pubkey,
+    pub token_address_to: Pubkey,
+    pub amount_to: u64,
+    pub chain_from: u64,
+    pub chain_to: u64,
+    pub event_id: u64,
+    pub expires_at: u64,
+    pub signer_epoch: u64,
+    pub flags: [u8; 32],
+    pub flag_data: Vec<u8>,
+    pub receipt_hash: [u8; 32],  // `SourceRef.receipt_hash`, hash of the AMB receipt
+    pub source_tx_hash: [u8; 32],  // `SourceRef.tx_hash`, AMB transaction of the receipt
+    pub slot: u64,
+}
+
+#[event]
+pub struct RoleGranted {
+    pub member: Pubkey,
+    pub role: Role,
+    pub sender: Pubkey,
+}
+
+#[event]
+pub struct RoleRevoked {
+    pub member: Pubkey,
+    pub role: Role,
+    pub sender: Pubkey,
+}
+
+#[event]
+pub struct OperationQueued {
+    pub id: u64,
+    pub operation: TimelockOperation,
+    pub proposer: Pubkey,
+    pub eta: u64,
+}
+
+#[event]
+pub struct OperationCancelled {
+    pub id: u64,
+    pub sender: Pubkey,
+}
+
+#[event]
+pub struct OperationExecuted {
+    pub id: u64,
+    pub operation: TimelockOperation,
+}
+
+#[event]
+pub struct NativeSendAmountChanged {
+    pub sender: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct PauseChanged {
+    pub sender: Pubkey,
+    pub pause_flags: u8,
+    pub reason: u16,
+}
+
+#[event]
+pub struct EmergencyPaused {
+    pub guardian: Pubkey,
+    pub token: Option<Pubkey>,  // None if the whole bridge is paused
+    pub reason: u16,
+}
+
+#[event]
+pub struct GuardiansChanged {
+    pub sender: Pubkey,
+    pub guardians: Vec<Pubkey>,
+}
+
+#[event]
+pub struct TokenInitialized {
+    pub sender: Pubkey,
+    pub token: Pubkey,
+    pub amb_token: [u8; 20],
+    pub amb_decimals: u8,
+    pub is_mintable: bool,
+}
+
+#[event]
+pub struct TokenPauseChanged {
+    pub sender: Pubkey,
+    pub token: Pubkey,
+    pub paused: bool,
+}
+
+#[event]
+pub struct CallTargetChanged {
+    pub program_id: Pubkey,
+    pub allowed: bool,
+}
+
+#[event]
+pub struct TokenMigrated {
+    pub mint: Pubkey,
+    pub successor_program: Pubkey,
+    pub new_authority: Pubkey,  // successor's token PDA, now holds the mint (and freeze) authority
+}
+
+#[event]
+pub struct MessageSent {
+    pub sender: Pubkey,
+    pub target: [u8; 20],
+    pub chain_from: u64,
+    pub chain_to: u64,
+    pub event_id: u64,  // shared sequence with `SendEvent`
+    pub payload: Vec<u8>,
+}
+
+#[event]
+pub struct MessageReceived {
+    pub sender: [u8; 20],
+    pub target: Pubkey,
+    pub chain_from: u64,
+    pub chain_to: u64,
+    pub event_id: u64,
+    pub payload: Vec<u8>,
+}
+
+#[event]
+pub struct CallExecuted {
+    pub event_id: u64,
+    pub program_id: Pubkey,
+    pub amount: u64,
+    pub refunded: u64,  // tokens the target didn't take, sent to the receiver
+}
+
+#[event]
+pub struct CallSkipped {
+    pub event_id: u64,
+    pub program_id: Pubkey,
+}
+
+#[event]
+pub struct SendRefunded {
+    pub event_id: u64,
+    pub sender: Pubkey,
+    pub token: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct NonceSkipped {
+    pub receiver: Pubkey,
+    pub from_nonce: u64,
+    pub to_nonce: u64,
+    pub voided_event_ids: Vec<u64>,
+}
+
+#[event]
+pub struct StateInitialized {
+    pub admin: Pubkey,
+    pub send_signer: Pubkey,
+    pub receive_signer: Pubkey,
+    pub timelock_delay: u64,
+}
+
+#[event]
+pub struct StateMigrated {
+    pub sender: Pubkey,
+    pub version: u8,
+    pub timelock_delay: u64,
+}
+
+#[event]
+pub struct SendAccumulatorInitialized {
+    pub sender: Pubkey,
+    pub first_event_id: u64,
+    pub send_root: [u8; 32],
+}
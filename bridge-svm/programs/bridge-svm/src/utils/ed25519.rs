@@ -0,0 +1,124 @@
+use crate::structs::CustomError;
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{
+    ed25519_program::ID as ED25519_ID,
+    sysvar::instructions::load_instruction_at_checked,
+};
+
+// Ed25519 precompile instruction data: num_signatures (u8), padding (u8), then for each signature
+// signature_offset, signature_instruction_index, public_key_offset, public_key_instruction_index,
+// message_data_offset, message_data_size, message_instruction_index (u16, little-endian each).
+const SIGNATURE_OFFSETS_START: usize = 2;
+const SIGNATURE_OFFSETS_SIZE: usize = 14;
+const PUBKEY_SIZE: usize = 32;
+
+// instruction index meaning "the precompile instruction itself"
+const CURRENT_INSTRUCTION: u16 = u16::MAX;
+
+// (signer, message) pairs verified by a precompile instruction.
+// Data referenced from other instructions isn't supported, it would have to be loaded and checked too.
+pub fn parse_ed25519_instruction(data: &[u8]) -> Result<Vec<(Pubkey, &[u8])>> {
+    let num_signatures = *data.first().ok_or(CustomError::InvalidSignature)? as usize;
+
+    let mut signatures = Vec::with_capacity(num_signatures);
+    for i in 0..num_signatures {
+        let start = SIGNATURE_OFFSETS_START + i * SIGNATURE_OFFSETS_SIZE;
+        let offsets = data.get(start..start + SIGNATURE_OFFSETS_SIZE).ok_or(CustomError::InvalidSignature)?;
+        let field = |n: usize| u16::from_le_bytes([offsets[2 * n], offsets[2 * n + 1]]);
+        let [_, signature_ix, pubkey_offset, pubkey_ix, message_offset, message_size, message_ix] =
+            [0, 1, 2, 3, 4, 5, 6].map(field);
+
+        require!(
+            signature_ix == CURRENT_INSTRUCTION && pubkey_ix == CURRENT_INSTRUCTION && message_ix == CURRENT_INSTRUCTION,
+            CustomError::InvalidSignature
+        );
+
+        let pubkey_offset = pubkey_offset as usize;
+        let pubkey = data.get(pubkey_offset..pubkey_offset + PUBKEY_SIZE).ok_or(CustomError::InvalidSignature)?;
+        let message_offset = message_offset as usize;
+        let message = data
+            .get(message_offset..message_offset + message_size as usize)
+            .ok_or(CustomError::InvalidSignature)?;
+
+        signatures.push((Pubkey::try_from(pubkey).unwrap(), message));
+    }
+    Ok(signatures)
+}
+
+// Looks for a precompile instruction anywhere in the transaction whose signatures satisfy `is_valid`.
+// Unlike `get_instruction_relative(-1)` this also works when the bridge is called through CPI,
+// as the sysvar only lists top-level instructions.
+pub fn find_ed25519_signatures(
+    ix_sysvar: &AccountInfo,
+    is_valid: impl Fn(&[(Pubkey, &[u8])]) -> bool,
+) -> Result<()> {
+    let mut index = 0;
+    while let Ok(ix) = load_instruction_at_checked(index, ix_sysvar) {
+        if ix.program_id == ED25519_ID {
+            if let Ok(signatures) = parse_ed25519_instruction(&ix.data) {
+                if is_valid(&signatures) {
+                    return Ok(());
+                }
+            }
+        }
+        index += 1;
+    }
+    err!(CustomError::InvalidSignature)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // same layout as `verifySignatureInstruction` in the sdk
+    fn ed25519_data(pubkeys: &[Pubkey], message: &[u8], instruction_index: u16) -> Vec<u8> {
+        let n = pubkeys.len();
+        let signatures_offset = SIGNATURE_OFFSETS_START + n * SIGNATURE_OFFSETS_SIZE;
+        let pubkeys_offset = signatures_offset + n * 64;
+        let message_offset = pubkeys_offset + n * PUBKEY_SIZE;
+
+        let mut data = vec![n as u8, 0];
+        for i in 0..n {
+            for field in [
+                signatures_offset + i * 64,
+                instruction_index as usize,
+                pubkeys_offset + i * PUBKEY_SIZE,
+                instruction_index as usize,
+                message_offset,
+                message.len(),
+                instruction_index as usize,
+            ] {
+                data.extend_from_slice(&(field as u16).to_le_bytes());
+            }
+        }
+        data.extend(vec![0; n * 64]);
+        pubkeys.iter().for_each(|pubkey| data.extend_from_slice(pubkey.as_ref()));
+        data.extend_from_slice(message);
+        data
+    }
+
+    #[test]
+    fn test_parse() {
+        let pubkeys = [Pubkey::new_unique(), Pubkey::new_unique()];
+        let message = [7u8; 32];
+        let data = ed25519_data(&pubkeys, &message, CURRENT_INSTRUCTION);
+
+        let signatures = parse_ed25519_instruction(&data).unwrap();
+        assert_eq!(signatures, vec![(pubkeys[0], &message[..]), (pubkeys[1], &message[..])]);
+    }
+
+    #[test]
+    fn test_parse_rejects_other_instructions() {
+        let data = ed25519_data(&[Pubkey::new_unique()], &[7u8; 32], 0);
+        assert!(parse_ed25519_instruction(&data).is_err());
+    }
+
+    #[test]
+    fn test_parse_rejects_out_of_bounds() {
+        let data = ed25519_data(&[Pubkey::new_unique()], &[7u8; 32], CURRENT_INSTRUCTION);
+        assert!(parse_ed25519_instruction(&data[..data.len() - 1]).is_err());
+        assert!(parse_ed25519_instruction(&data[..10]).is_err());
+        assert!(parse_ed25519_instruction(&[]).is_err());
+    }
+}
@@ -0,0 +1,30 @@
+// With the `event-cpi` feature events are emitted as a self-CPI signed by the event authority PDA,
+// so they are in the transaction's inner instructions, which aren't truncated or dropped like logs.
+// Without it they are logged with `emit!`. Contexts that emit need `#[cfg_attr(feature = "event-cpi", event_cpi)]`.
+macro_rules! emit_event {
+    ($ctx:expr, $event:expr) => {{
+        #[cfg(feature = "event-cpi")]
+        $crate::utils::events::emit_cpi_event(&$ctx.accounts.event_authority, $ctx.bumps.event_authority, $event)?;
+        #[cfg(not(feature = "event-cpi"))]
+        anchor_lang::prelude::emit!($event);
+    }};
+}
+pub(crate) use emit_event;
+
+#[cfg(feature = "event-cpi")]
+pub const EVENT_AUTHORITY_SEED: &[u8] = b"__event_authority";
+
+// Same as `emit_cpi!`, which needs `ctx` by that name.
+#[cfg(feature = "event-cpi")]
+pub fn emit_cpi_event<E: anchor_lang::Event>(
+    event_authority: &anchor_lang::prelude::AccountInfo,
+    bump: u8,
+    event: E,
+) -> anchor_lang::Result<()> {
+    use anchor_lang::solana_program::{instruction::{AccountMeta, Instruction}, program::invoke_signed};
+
+    let data: Vec<u8> = anchor_lang::event::EVENT_IX_TAG_LE.into_iter().chain(event.data()).collect();
+    let ix = Instruction::new_with_bytes(crate::ID, &data, vec![AccountMeta::new_readonly(*event_authority.key, true)]);
+    invoke_signed(&ix, std::slice::from_ref(event_authority), &[&[EVENT_AUTHORITY_SEED, &[bump]]])?;
+    Ok(())
+}
@@ -0,0 +1,259 @@
+use crate::structs::CustomError;
+use anchor_lang::prelude::*;
+
+// Bits of the 256-bit big-endian `flags`, same as EVM `BridgeFlags`.
+// Bits 0-64 are for the sender side, bits 65-128 for the receiver side.
+pub const SENDER_IS_TXORIGIN: u32 = 0;
+pub const SEND_WITH_PERMIT: u32 = 1;
+pub const SHOULD_WRAP: u32 = 2;
+pub const SHOULD_UNWRAP: u32 = 65;
+pub const SEND_NATIVE_TO_RECEIVER: u32 = 66;
+pub const SHOULD_RESTAKE: u32 = 67;
+
+// EVM `claim` gets the receiver bits shifted down by this
+pub const RECEIVER_SHIFT: u32 = 65;
+
+// Receiver bits this bridge acts on (or that the EVM side does, for sends); `SHOULD_RESTAKE` is unused there too.
+const SUPPORTED_RECEIVER_BITS: u64 =
+    1 << (SHOULD_UNWRAP - RECEIVER_SHIFT) | 1 << (SEND_NATIVE_TO_RECEIVER - RECEIVER_SHIFT);
+
+pub const MAX_FLAG_DATA_LEN: usize = 512;
+
+// `flag_data` is empty, or a version byte followed by TLV entries: tag (u8), length (u16, little-endian), value.
+// On receive it's prefixed with the receiver nonce (u64, big-endian).
+pub const FLAG_DATA_VERSION: u8 = 1;
+pub const TAG_CALL: u8 = 1;  // borsh `CallData`, see `receive_and_call`
+pub const TAG_TO_TOKEN_ACCOUNT: u8 = 2;  // empty; `ReceivePayload.to` is a token account, not an owner, see `receive_to`
+
+const RECEIVE_TAGS: &[u8] = &[TAG_CALL, TAG_TO_TOKEN_ACCOUNT];
+
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct BridgeFlags {
+    hi: u128,  // bits 128-255
+    lo: u128,  // bits 0-127
+}
+
+impl BridgeFlags {
+    pub fn from_bytes(bytes: &[u8; 32]) -> Self {
+        Self {
+            hi: u128::from_be_bytes(bytes[..16].try_into().unwrap()),
+            lo: u128::from_be_bytes(bytes[16..].try_into().unwrap()),
+        }
+    }
+
+    pub fn to_bytes(self) -> [u8; 32] {
+        let mut bytes = [0u8; 32];
+        bytes[..16].copy_from_slice(&self.hi.to_be_bytes());
+        bytes[16..].copy_from_slice(&self.lo.to_be_bytes());
+        bytes
+    }
+
+    pub fn with(self, bit: u32) -> Self {
+        match bit {
+            0..=127 => Self { lo: self.lo | 1 << bit, ..self },
+            _ => Self { hi: self.hi | 1 << (bit - 128), ..self },
+        }
+    }
+
+    pub fn has(self, bit: u32) -> bool {
+        match bit {
+            0..=127 => self.lo >> bit & 1 == 1,
+            _ => self.hi >> (bit - 128) & 1 == 1,
+        }
+    }
+
+    pub fn sender_bits(self) -> u128 {
+        self.lo & ((1 << RECEIVER_SHIFT) - 1)
+    }
+
+    // bits 65-128, as EVM `claim` sees them
+    pub fn receiver_bits(self) -> u64 {
+        (self.lo >> RECEIVER_SHIFT) as u64 | ((self.hi & 1) as u64) << 63
+    }
+
+    // bits 129-255 aren't defined
+    fn reserved_bits(self) -> u128 {
+        self.hi >> 1
+    }
+
+    // Sender bits come from the source chain and don't matter here.
+    pub fn for_receive(self) -> Result<ReceiveFlags> {
+        require!(self.reserved_bits() == 0, CustomError::UnsupportedFlags);
+        require!(self.receiver_bits() & !SUPPORTED_RECEIVER_BITS == 0, CustomError::UnsupportedFlags);
+        Ok(ReceiveFlags {
+            should_unwrap: self.has(SHOULD_UNWRAP),
+            send_native_to_receiver: self.has(SEND_NATIVE_TO_RECEIVER),
+        })
+    }
+
+    // None of the sender bits (tx.origin, permit, wrap) mean anything on Solana.
+    pub fn check_send(self) -> Result<()> {
+        require!(self.reserved_bits() == 0, CustomError::UnsupportedFlags);
+        require!(self.sender_bits() == 0, CustomError::UnsupportedFlags);
+        require!(self.receiver_bits() & !SUPPORTED_RECEIVER_BITS == 0, CustomError::UnsupportedFlags);
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct ReceiveFlags {
+    pub should_unwrap: bool,  // handled by the client, it unwraps wSOL after receive
+    pub send_native_to_receiver: bool,
+}
+
+
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct FlagData<'a> {
+    entries: Vec<(u8, &'a [u8])>,
+}
+
+impl<'a> FlagData<'a> {
+    pub fn parse(data: &'a [u8]) -> Result<Self> {
+        require!(data.len() <= MAX_FLAG_DATA_LEN, CustomError::FlagDataTooLong);
+        let Some((&version, mut rest)) = data.split_first() else {
+            return Ok(Self::default());
+        };
+        require!(version == FLAG_DATA_VERSION, CustomError::InvalidFlagData);
+
+        let mut entries: Vec<(u8, &[u8])> = vec![];
+        while let [tag, len_lo, len_hi, tail @ ..] = rest {
+            let len = u16::from_le_bytes([*len_lo, *len_hi]) as usize;
+            let value = tail.get(..len).ok_or(CustomError::InvalidFlagData)?;
+            require!(entries.iter().all(|(t, _)| t != tag), CustomError::InvalidFlagData);
+            entries.push((*tag, value));
+            rest = &tail[len..];
+        }
+        // trailing bytes too short for an entry header
+        require!(rest.is_empty(), CustomError::InvalidFlagData);
+
+        Ok(Self { entries })
+    }
+
+    pub fn get(&self, tag: u8) -> Option<&'a [u8]> {
+        self.entries.iter().find(|(t, _)| *t == tag).map(|(_, value)| *value)
+    }
+
+    pub fn has(&self, tag: u8) -> bool {
+        self.get(tag).is_some()
+    }
+
+    pub fn tags(&self) -> impl Iterator<Item = u8> + '_ {
+        self.entries.iter().map(|(tag, _)| *tag)
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ReceiveFlagData<'a> {
+    pub nonce: u64,
+    pub data: FlagData<'a>,
+}
+
+impl<'a> ReceiveFlagData<'a> {
+    pub fn parse(data: &'a [u8]) -> Result<Self> {
+        require!(data.len() <= MAX_FLAG_DATA_LEN, CustomError::FlagDataTooLong);
+        let nonce = data.get(..8).ok_or(CustomError::InvalidFlagData)?;
+        let flag_data = FlagData::parse(&data[8..])?;
+        require!(flag_data.tags().all(|tag| RECEIVE_TAGS.contains(&tag)), CustomError::InvalidFlagData);
+
+        Ok(Self {
+            nonce: u64::from_be_bytes(nonce.try_into().unwrap()),
+            data: flag_data,
+        })
+    }
+}
+
+// Send `flag_data` is passed on to the other chain, only its shape and size are checked here.
+pub fn check_send_flags(flags: &[u8; 32], flag_data: &[u8]) -> Result<()> {
+    BridgeFlags::from_bytes(flags).check_send()?;
+    FlagData::parse(flag_data)?;
+    Ok(())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encode(entries: &[(u8, &[u8])]) -> Vec<u8> {
+        let mut data = vec![FLAG_DATA_VERSION];
+        for (tag, value) in entries {
+            data.push(*tag);
+            data.extend_from_slice(&(value.len() as u16).to_le_bytes());
+            data.extend_from_slice(value);
+        }
+        data
+    }
+
+    fn assert_error<T: std::fmt::Debug>(result: Result<T>, expected: CustomError) {
+        assert_eq!(result.unwrap_err(), expected.into());
+    }
+
+    #[test]
+    fn test_flags_layout() {
+        let flags = BridgeFlags::default().with(SEND_NATIVE_TO_RECEIVER);
+        let mut expected = [0u8; 32];
+        expected[31 - 8] = 1 << 2;
+        assert_eq!(flags.to_bytes(), expected);
+        assert_eq!(BridgeFlags::from_bytes(&expected), flags);
+        assert_eq!(flags.receiver_bits(), 0b10);
+        assert_eq!(flags.sender_bits(), 0);
+
+        let flags = BridgeFlags::default().with(SHOULD_WRAP).with(128);
+        assert_eq!(flags.sender_bits(), 0b100);
+        assert_eq!(flags.receiver_bits(), 1 << 63);
+    }
+
+    #[test]
+    fn test_receive_flags() {
+        let flags = BridgeFlags::default().with(SHOULD_UNWRAP).with(SENDER_IS_TXORIGIN).for_receive().unwrap();
+        assert!(flags.should_unwrap);
+        assert!(!flags.send_native_to_receiver);
+
+        assert_error(BridgeFlags::default().with(SHOULD_RESTAKE).for_receive(), CustomError::UnsupportedFlags);
+        assert_error(BridgeFlags::default().with(200).for_receive(), CustomError::UnsupportedFlags);
+    }
+
+    #[test]
+    fn test_send_flags() {
+        assert!(BridgeFlags::default().with(SEND_NATIVE_TO_RECEIVER).check_send().is_ok());
+        assert_error(BridgeFlags::default().with(SEND_WITH_PERMIT).check_send(), CustomError::UnsupportedFlags);
+        assert_error(BridgeFlags::default().with(SHOULD_RESTAKE).check_send(), CustomError::UnsupportedFlags);
+    }
+
+    #[test]
+    fn test_flag_data() {
+        assert_eq!(FlagData::parse(&[]).unwrap(), FlagData::default());
+
+        let data = encode(&[(TAG_CALL, &[1, 2, 3]), (7, &[])]);
+        let flag_data = FlagData::parse(&data).unwrap();
+        assert_eq!(flag_data.get(TAG_CALL), Some(&[1u8, 2, 3][..]));
+        assert_eq!(flag_data.get(7), Some(&[][..]));
+        assert_eq!(flag_data.get(2), None);
+
+        assert_error(FlagData::parse(&[2]), CustomError::InvalidFlagData);
+        assert_error(FlagData::parse(&data[..data.len() - 1]), CustomError::InvalidFlagData);
+        assert_error(FlagData::parse(&encode(&[(1, &[]), (1, &[])])), CustomError::InvalidFlagData);
+        assert_error(FlagData::parse(&encode(&[(1, &[0; MAX_FLAG_DATA_LEN])])), CustomError::FlagDataTooLong);
+    }
+
+    #[test]
+    fn test_receive_flag_data() {
+        let data = 5u64.to_be_bytes();
+        let parsed = ReceiveFlagData::parse(&data).unwrap();
+        assert_eq!(parsed.nonce, 5);
+        assert_eq!(parsed.data, FlagData::default());
+
+        let mut data = 6u64.to_be_bytes().to_vec();
+        data.extend(encode(&[(TAG_CALL, &[9])]));
+        let parsed = ReceiveFlagData::parse(&data).unwrap();
+        assert_eq!(parsed.nonce, 6);
+        assert_eq!(parsed.data.get(TAG_CALL), Some(&[9u8][..]));
+        assert!(!parsed.data.has(TAG_TO_TOKEN_ACCOUNT));
+
+        assert_error(ReceiveFlagData::parse(&[0; 7]), CustomError::InvalidFlagData);
+        let mut data = 6u64.to_be_bytes().to_vec();
+        data.extend(encode(&[(7, &[])]));
+        assert_error(ReceiveFlagData::parse(&data), CustomError::InvalidFlagData);
+    }
+}
@@ -0,0 +1,164 @@
+// Append-only Merkle tree of fixed depth, nodes are `keccak(left || right)` and empty leaves are zero.
+// On-chain only the frontier (`branch`) is kept, see `SendAccumulator`; proofs are built off-chain from all leaves.
+use anchor_lang::solana_program::keccak::hashv;
+
+pub const TREE_DEPTH: usize = 32;
+
+// `ZERO_HASHES[level]` is the root of an empty subtree of height `level`, checked by `test_zero_hashes`.
+pub const ZERO_HASHES: [[u8; 32]; TREE_DEPTH] = [
+    [0; 32],
+    [0xad, 0x32, 0x28, 0xb6, 0x76, 0xf7, 0xd3, 0xcd, 0x42, 0x84, 0xa5, 0x44, 0x3f, 0x17, 0xf1, 0x96, 0x2b, 0x36, 0xe4, 0x91, 0xb3, 0x0a, 0x40, 0xb2, 0x40, 0x58, 0x49, 0xe5, 0x97, 0xba, 0x5f, 0xb5],
+    [0xb4, 0xc1, 0x19, 0x51, 0x95, 0x7c, 0x6f, 0x8f, 0x64, 0x2c, 0x4a, 0xf6, 0x1c, 0xd6, 0xb2, 0x46, 0x40, 0xfe, 0xc6, 0xdc, 0x7f, 0xc6, 0x07, 0xee, 0x82, 0x06, 0xa9, 0x9e, 0x92, 0x41, 0x0d, 0x30],
+    [0x21, 0xdd, 0xb9, 0xa3, 0x56, 0x81, 0x5c, 0x3f, 0xac, 0x10, 0x26, 0xb6, 0xde, 0xc5, 0xdf, 0x31, 0x24, 0xaf, 0xba, 0xdb, 0x48, 0x5c, 0x9b, 0xa5, 0xa3, 0xe3, 0x39, 0x8a, 0x04, 0xb7, 0xba, 0x85],
+    [0xe5, 0x87, 0x69, 0xb3, 0x2a, 0x1b, 0xea, 0xf1, 0xea, 0x27, 0x37, 0x5a, 0x44, 0x09, 0x5a, 0x0d, 0x1f, 0xb6, 0x64, 0xce, 0x2d, 0xd3, 0x58, 0xe7, 0xfc, 0xbf, 0xb7, 0x8c, 0x26, 0xa1, 0x93, 0x44],
+    [0x0e, 0xb0, 0x1e, 0xbf, 0xc9, 0xed, 0x27, 0x50, 0x0c, 0xd4, 0xdf, 0xc9, 0x79, 0x27, 0x2d, 0x1f, 0x09, 0x13, 0xcc, 0x9f, 0x66, 0x54, 0x0d, 0x7e, 0x80, 0x05, 0x81, 0x11, 0x09, 0xe1, 0xcf, 0x2d],
+    [0x88, 0x7c, 0x22, 0xbd, 0x87, 0x50, 0xd3, 0x40, 0x16, 0xac, 0x3c, 0x66, 0xb5, 0xff, 0x10, 0x2d, 0xac, 0xdd, 0x73, 0xf6, 0xb0, 0x14, 0xe7, 0x10, 0xb5, 0x1e, 0x80, 0x22, 0xaf, 0x9a, 0x19, 0x68],
+    [0xff, 0xd7, 0x01, 0x57, 0xe4, 0x80, 0x63, 0xfc, 0x33, 0xc9, 0x7a, 0x05, 0x0f, 0x7f, 0x64, 0x02, 0x33, 0xbf, 0x64, 0x6c, 0xc9, 0x8d, 0x95, 0x24, 0xc6, 0xb9, 0x2b, 0xcf, 0x3a, 0xb5, 0x6f, 0x83],
+    [0x98, 0x67, 0xcc, 0x5f, 0x7f, 0x19, 0x6b, 0x93, 0xba, 0xe1, 0xe2, 0x7e, 0x63, 0x20, 0x74, 0x24, 0x45, 0xd2, 0x90, 0xf2, 0x26, 0x38, 0x27, 0x49, 0x8b, 0x54, 0xfe, 0xc5, 0x39, 0xf7, 0x56, 0xaf],
+    [0xce, 0xfa, 0xd4, 0xe5, 0x08, 0xc0, 0x98, 0xb9, 0xa7, 0xe1, 0xd8, 0xfe, 0xb1, 0x99, 0x55, 0xfb, 0x02, 0xba, 0x96, 0x75, 0x58, 0x50, 0x78, 0x71, 0x09, 0x69, 0xd3, 0x44, 0x0f, 0x50, 0x54, 0xe0],
+    [0xf9, 0xdc, 0x3e, 0x7f, 0xe0, 0x16, 0xe0, 0x50, 0xef, 0xf2, 0x60, 0x33, 0x4f, 0x18, 0xa5, 0xd4, 0xfe, 0x39, 0x1d, 0x82, 0x09, 0x23, 0x19, 0xf5, 0x96, 0x4f, 0x2e, 0x2e, 0xb7, 0xc1, 0xc3, 0xa5],
+    [0xf8, 0xb1, 0x3a, 0x49, 0xe2, 0x82, 0xf6, 0x09, 0xc3, 0x17, 0xa8, 0x33, 0xfb, 0x8d, 0x97, 0x6d, 0x11, 0x51, 0x7c, 0x57, 0x1d, 0x12, 0x21, 0xa2, 0x65, 0xd2, 0x5a, 0xf7, 0x78, 0xec, 0xf8, 0x92],
+    [0x34, 0x90, 0xc6, 0xce, 0xeb, 0x45, 0x0a, 0xec, 0xdc, 0x82, 0xe2, 0x82, 0x93, 0x03, 0x1d, 0x10, 0xc7, 0xd7, 0x3b, 0xf8, 0x5e, 0x57, 0xbf, 0x04, 0x1a, 0x97, 0x36, 0x0a, 0xa2, 0xc5, 0xd9, 0x9c],
+    [0xc1, 0xdf, 0x82, 0xd9, 0xc4, 0xb8, 0x74, 0x13, 0xea, 0xe2, 0xef, 0x04, 0x8f, 0x94, 0xb4, 0xd3, 0x55, 0x4c, 0xea, 0x73, 0xd9, 0x2b, 0x0f, 0x7a, 0xf9, 0x6e, 0x02, 0x71, 0xc6, 0x91, 0xe2, 0xbb],
+    [0x5c, 0x67, 0xad, 0xd7, 0xc6, 0xca, 0xf3, 0x02, 0x25, 0x6a, 0xde, 0xdf, 0x7a, 0xb1, 0x14, 0xda, 0x0a, 0xcf, 0xe8, 0x70, 0xd4, 0x49, 0xa3, 0xa4, 0x89, 0xf7, 0x81, 0xd6, 0x59, 0xe8, 0xbe, 0xcc],
+    [0xda, 0x7b, 0xce, 0x9f, 0x4e, 0x86, 0x18, 0xb6, 0xbd, 0x2f, 0x41, 0x32, 0xce, 0x79, 0x8c, 0xdc, 0x7a, 0x60, 0xe7, 0xe1, 0x46, 0x0a, 0x72, 0x99, 0xe3, 0xc6, 0x34, 0x2a, 0x57, 0x96, 0x26, 0xd2],
+    [0x27, 0x33, 0xe5, 0x0f, 0x52, 0x6e, 0xc2, 0xfa, 0x19, 0xa2, 0x2b, 0x31, 0xe8, 0xed, 0x50, 0xf2, 0x3c, 0xd1, 0xfd, 0xf9, 0x4c, 0x91, 0x54, 0xed, 0x3a, 0x76, 0x09, 0xa2, 0xf1, 0xff, 0x98, 0x1f],
+    [0xe1, 0xd3, 0xb5, 0xc8, 0x07, 0xb2, 0x81, 0xe4, 0x68, 0x3c, 0xc6, 0xd6, 0x31, 0x5c, 0xf9, 0x5b, 0x9a, 0xde, 0x86, 0x41, 0xde, 0xfc, 0xb3, 0x23, 0x72, 0xf1, 0xc1, 0x26, 0xe3, 0x98, 0xef, 0x7a],
+    [0x5a, 0x2d, 0xce, 0x0a, 0x8a, 0x7f, 0x68, 0xbb, 0x74, 0x56, 0x0f, 0x8f, 0x71, 0x83, 0x7c, 0x2c, 0x2e, 0xbb, 0xcb, 0xf7, 0xff, 0xfb, 0x42, 0xae, 0x18, 0x96, 0xf1, 0x3f, 0x7c, 0x74, 0x79, 0xa0],
+    [0xb4, 0x6a, 0x28, 0xb6, 0xf5, 0x55, 0x40, 0xf8, 0x94, 0x44, 0xf6, 0x3d, 0xe0, 0x37, 0x8e, 0x3d, 0x12, 0x1b, 0xe0, 0x9e, 0x06, 0xcc, 0x9d, 0xed, 0x1c, 0x20, 0xe6, 0x58, 0x76, 0xd3, 0x6a, 0xa0],
+    [0xc6, 0x5e, 0x96, 0x45, 0x64, 0x47, 0x86, 0xb6, 0x20, 0xe2, 0xdd, 0x2a, 0xd6, 0x48, 0xdd, 0xfc, 0xbf, 0x4a, 0x7e, 0x5b, 0x1a, 0x3a, 0x4e, 0xcf, 0xe7, 0xf6, 0x46, 0x67, 0xa3, 0xf0, 0xb7, 0xe2],
+    [0xf4, 0x41, 0x85, 0x88, 0xed, 0x35, 0xa2, 0x45, 0x8c, 0xff, 0xeb, 0x39, 0xb9, 0x3d, 0x26, 0xf1, 0x8d, 0x2a, 0xb1, 0x3b, 0xdc, 0xe6, 0xae, 0xe5, 0x8e, 0x7b, 0x99, 0x35, 0x9e, 0xc2, 0xdf, 0xd9],
+    [0x5a, 0x9c, 0x16, 0xdc, 0x00, 0xd6, 0xef, 0x18, 0xb7, 0x93, 0x3a, 0x6f, 0x8d, 0xc6, 0x5c, 0xcb, 0x55, 0x66, 0x71, 0x38, 0x77, 0x6f, 0x7d, 0xea, 0x10, 0x10, 0x70, 0xdc, 0x87, 0x96, 0xe3, 0x77],
+    [0x4d, 0xf8, 0x4f, 0x40, 0xae, 0x0c, 0x82, 0x29, 0xd0, 0xd6, 0x06, 0x9e, 0x5c, 0x8f, 0x39, 0xa7, 0xc2, 0x99, 0x67, 0x7a, 0x09, 0xd3, 0x67, 0xfc, 0x7b, 0x05, 0xe3, 0xbc, 0x38, 0x0e, 0xe6, 0x52],
+    [0xcd, 0xc7, 0x25, 0x95, 0xf7, 0x4c, 0x7b, 0x10, 0x43, 0xd0, 0xe1, 0xff, 0xba, 0xb7, 0x34, 0x64, 0x8c, 0x83, 0x8d, 0xfb, 0x05, 0x27, 0xd9, 0x71, 0xb6, 0x02, 0xbc, 0x21, 0x6c, 0x96, 0x19, 0xef],
+    [0x0a, 0xbf, 0x5a, 0xc9, 0x74, 0xa1, 0xed, 0x57, 0xf4, 0x05, 0x0a, 0xa5, 0x10, 0xdd, 0x9c, 0x74, 0xf5, 0x08, 0x27, 0x7b, 0x39, 0xd7, 0x97, 0x3b, 0xb2, 0xdf, 0xcc, 0xc5, 0xee, 0xb0, 0x61, 0x8d],
+    [0xb8, 0xcd, 0x74, 0x04, 0x6f, 0xf3, 0x37, 0xf0, 0xa7, 0xbf, 0x2c, 0x8e, 0x03, 0xe1, 0x0f, 0x64, 0x2c, 0x18, 0x86, 0x79, 0x8d, 0x71, 0x80, 0x6a, 0xb1, 0xe8, 0x88, 0xd9, 0xe5, 0xee, 0x87, 0xd0],
+    [0x83, 0x8c, 0x56, 0x55, 0xcb, 0x21, 0xc6, 0xcb, 0x83, 0x31, 0x3b, 0x5a, 0x63, 0x11, 0x75, 0xdf, 0xf4, 0x96, 0x37, 0x72, 0xcc, 0xe9, 0x10, 0x81, 0x88, 0xb3, 0x4a, 0xc8, 0x7c, 0x81, 0xc4, 0x1e],
+    [0x66, 0x2e, 0xe4, 0xdd, 0x2d, 0xd7, 0xb2, 0xbc, 0x70, 0x79, 0x61, 0xb1, 0xe6, 0x46, 0xc4, 0x04, 0x76, 0x69, 0xdc, 0xb6, 0x58, 0x4f, 0x0d, 0x8d, 0x77, 0x0d, 0xaf, 0x5d, 0x7e, 0x7d, 0xeb, 0x2e],
+    [0x38, 0x8a, 0xb2, 0x0e, 0x25, 0x73, 0xd1, 0x71, 0xa8, 0x81, 0x08, 0xe7, 0x9d, 0x82, 0x0e, 0x98, 0xf2, 0x6c, 0x0b, 0x84, 0xaa, 0x8b, 0x2f, 0x4a, 0xa4, 0x96, 0x8d, 0xbb, 0x81, 0x8e, 0xa3, 0x22],
+    [0x93, 0x23, 0x7c, 0x50, 0xba, 0x75, 0xee, 0x48, 0x5f, 0x4c, 0x22, 0xad, 0xf2, 0xf7, 0x41, 0x40, 0x0b, 0xdf, 0x8d, 0x6a, 0x9c, 0xc7, 0xdf, 0x7e, 0xca, 0xe5, 0x76, 0x22, 0x16, 0x65, 0xd7, 0x35],
+    [0x84, 0x48, 0x81, 0x8b, 0xb4, 0xae, 0x45, 0x62, 0x84, 0x9e, 0x94, 0x9e, 0x17, 0xac, 0x16, 0xe0, 0xbe, 0x16, 0x68, 0x8e, 0x15, 0x6b, 0x5c, 0xf1, 0x5e, 0x09, 0x8c, 0x62, 0x7c, 0x00, 0x56, 0xa9],
+];
+
+pub fn hash_pair(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    hashv(&[left, right]).to_bytes()
+}
+
+// Appends `leaf` at `index` (the number of leaves before it) and returns the new root.
+pub fn append_leaf(branch: &mut [[u8; 32]; TREE_DEPTH], index: u64, leaf: [u8; 32]) -> [u8; 32] {
+    let mut node = leaf;
+    for (level, sibling) in branch.iter_mut().enumerate() {
+        if (index >> level) & 1 == 1 {
+            node = hash_pair(sibling, &node);
+        } else {
+            // the left subtree of later leaves, until it's full
+            *sibling = node;
+            node = hash_pair(&node, &ZERO_HASHES[level]);
+        }
+    }
+    node
+}
+
+pub fn empty_root() -> [u8; 32] {
+    let top = &ZERO_HASHES[TREE_DEPTH - 1];
+    hash_pair(top, top)
+}
+
+// Root of a tree holding `leaves`, same as after appending them one by one.
+pub fn merkle_root(leaves: &[[u8; 32]]) -> [u8; 32] {
+    let mut root = empty_root();
+    let mut branch = [[0u8; 32]; TREE_DEPTH];
+    for (index, leaf) in leaves.iter().enumerate() {
+        root = append_leaf(&mut branch, index as u64, *leaf);
+    }
+    root
+}
+
+// Siblings of `leaves[index]` from the bottom up, for `verify_merkle_proof` against `merkle_root(leaves)`.
+pub fn merkle_proof(leaves: &[[u8; 32]], index: usize) -> Vec<[u8; 32]> {
+    assert!(index < leaves.len(), "leaf index out of range");
+    let mut proof = Vec::with_capacity(TREE_DEPTH);
+    let mut level = leaves.to_vec();
+    let mut position = index;
+    for zero in ZERO_HASHES.iter() {
+        proof.push(level.get(position ^ 1).copied().unwrap_or(*zero));
+        level = level
+            .chunks(2)
+            .map(|pair| hash_pair(&pair[0], pair.get(1).unwrap_or(zero)))
+            .collect();
+        position /= 2;
+    }
+    proof
+}
+
+pub fn verify_merkle_proof(leaf: [u8; 32], index: u64, proof: &[[u8; 32]], root: [u8; 32]) -> bool {
+    if proof.len() != TREE_DEPTH {
+        return false;
+    }
+    let node = proof.iter().enumerate().fold(leaf, |node, (level, sibling)| {
+        if (index >> level) & 1 == 1 {
+            hash_pair(sibling, &node)
+        } else {
+            hash_pair(&node, sibling)
+        }
+    });
+    node == root
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn leaves(count: u8) -> Vec<[u8; 32]> {
+        (1..=count).map(|i| [i; 32]).collect()
+    }
+
+    #[test]
+    fn test_append_matches_full_tree() {
+        let leaves = leaves(9);
+        let mut branch = [[0u8; 32]; TREE_DEPTH];
+        for (index, leaf) in leaves.iter().enumerate() {
+            let root = append_leaf(&mut branch, index as u64, *leaf);
+            assert_eq!(root, merkle_root(&leaves[..=index]));
+        }
+
+        // two leaves by hand
+        let mut node = hash_pair(&[1; 32], &[2; 32]);
+        let mut zero = hash_pair(&[0; 32], &[0; 32]);
+        for _ in 1..TREE_DEPTH {
+            node = hash_pair(&node, &zero);
+            zero = hash_pair(&zero, &zero);
+        }
+        assert_eq!(merkle_root(&leaves[..2]), node);
+        assert_eq!(merkle_root(&[]), empty_root());
+    }
+
+    #[test]
+    fn test_zero_hashes() {
+        let mut zero = [0u8; 32];
+        for expected in ZERO_HASHES.iter() {
+            assert_eq!(&zero, expected);
+            zero = hash_pair(&zero, &zero);
+        }
+        assert_eq!(zero, empty_root());
+    }
+
+    #[test]
+    fn test_merkle_proof() {
+        let leaves = leaves(7);
+        let root = merkle_root(&leaves);
+        for (index, leaf) in leaves.iter().enumerate() {
+            let proof = merkle_proof(&leaves, index);
+            assert!(verify_merkle_proof(*leaf, index as u64, &proof, root));
+            assert!(!verify_merkle_proof([0xff; 32], index as u64, &proof, root));
+            assert!(!verify_merkle_proof(*leaf, index as u64 ^ 1, &proof, root));
+            assert!(!verify_merkle_proof(*leaf, index as u64, &proof[1..], root));
+        }
+
+        // proofs against an older root stay valid for it
+        let old_root = merkle_root(&leaves[..3]);
+        assert!(verify_merkle_proof(leaves[2], 2, &merkle_proof(&leaves[..3], 2), old_root));
+        assert!(!verify_merkle_proof(leaves[2], 2, &merkle_proof(&leaves, 2), old_root));
+    }
+}
@@ -1,3 +1,8 @@
+pub mod ed25519;
+pub mod events;
+pub mod flags;
+pub mod merkle;
+pub mod receipt;
 pub mod scale_amount;
 pub mod transfer;
 
@@ -0,0 +1,121 @@
+use crate::structs::{MessageSent, SendEvent};
+use crate::utils::flags::BridgeFlags;
+use anchor_lang::solana_program::keccak::hashv;
+
+// EVM `ReceiptUtils.toHash` of the receipt the AMB side builds from a `SendEvent`:
+// `toEthSignedMessageHash(keccak256(abi.encodePacked(to, tokenAddressTo, amountTo, chainFrom, chainTo, eventId, flags >> 65, data)))`.
+pub fn send_receipt_hash(event: &SendEvent) -> [u8; 32] {
+    // sends have no flags above bit 128, so `flags >> 65` is just the receiver bits
+    let receiver_flags = u256(BridgeFlags::from_bytes(&event.flags).receiver_bits());
+    let message = hashv(&[
+        &address(&event.to),
+        &address(&event.token_address_to),
+        &event.amount_to,
+        &u256(event.chain_from),
+        &u256(event.chain_to),
+        &u256(event.event_id),
+        &receiver_flags,
+        &event.flag_data,
+    ]);
+    hashv(&[b"\x19Ethereum Signed Message:\n32", &message.to_bytes()]).to_bytes()
+}
+
+// Leaf of a message in the `SendAccumulator`. AMB has no receipt for messages, so it's not an EVM hash;
+// the `message` prefix keeps it apart from `send_receipt_hash`, whose preimage starts with the eth-signed prefix.
+pub fn message_leaf_hash(event: &MessageSent) -> [u8; 32] {
+    hashv(&[
+        b"message",
+        event.sender.as_ref(),
+        &address(&event.target),
+        &u256(event.chain_from),
+        &u256(event.chain_to),
+        &u256(event.event_id),
+        &event.payload,
+    ])
+    .to_bytes()
+}
+
+fn address(address: &[u8; 20]) -> [u8; 32] {
+    let mut word = [0u8; 32];
+    word[12..].copy_from_slice(address);
+    word
+}
+
+fn u256(value: u64) -> [u8; 32] {
+    let mut word = [0u8; 32];
+    word[24..].copy_from_slice(&value.to_be_bytes());
+    word
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::flags::SHOULD_UNWRAP;
+    use anchor_lang::prelude::Pubkey;
+    use anchor_lang::solana_program::keccak::hash;
+
+    fn event(flags: [u8; 32], flag_data: Vec<u8>) -> SendEvent {
+        SendEvent {
+            from: Pubkey::new_unique(),
+            to: [1; 20],
+            token_address_from: Pubkey::new_unique(),
+            token_address_to: [2; 20],
+            amount_from: 5,
+            amount_to: [3; 32],
+            chain_from: 7,
+            chain_to: 8,
+            event_id: 9,
+            flags,
+            flag_data,
+        }
+    }
+
+    #[test]
+    fn test_send_receipt_hash() {
+        let flags = BridgeFlags::default().with(SHOULD_UNWRAP).to_bytes();
+        let mut packed = vec![];
+        packed.extend([0; 12]);
+        packed.extend([1; 20]);
+        packed.extend([0; 12]);
+        packed.extend([2; 20]);
+        packed.extend([3; 32]);
+        for value in [7u8, 8, 9, 1] {  // chain_from, chain_to, event_id, flags >> 65
+            packed.extend([0; 31]);
+            packed.push(value);
+        }
+        packed.extend([4, 5]);
+
+        let mut prefixed = b"\x19Ethereum Signed Message:\n32".to_vec();
+        prefixed.extend(hash(&packed).to_bytes());
+        assert_eq!(send_receipt_hash(&event(flags, vec![4, 5])), hash(&prefixed).to_bytes());
+
+        // sender-side fields aren't part of the hash
+        let mut other = event(flags, vec![4, 5]);
+        other.amount_from = 6;
+        assert_eq!(send_receipt_hash(&other), send_receipt_hash(&event(flags, vec![4, 5])));
+        assert_ne!(send_receipt_hash(&event(flags, vec![4])), send_receipt_hash(&event(flags, vec![4, 5])));
+    }
+
+    #[test]
+    fn test_message_leaf_hash() {
+        let message = MessageSent {
+            sender: Pubkey::new_from_array([6; 32]),
+            target: [1; 20],
+            chain_from: 7,
+            chain_to: 8,
+            event_id: 9,
+            payload: vec![4, 5],
+        };
+        let mut packed = b"message".to_vec();
+        packed.extend([6; 32]);
+        packed.extend([0; 12]);
+        packed.extend([1; 20]);
+        for value in [7u8, 8, 9] {  // chain_from, chain_to, event_id
+            packed.extend([0; 31]);
+            packed.push(value);
+        }
+        packed.extend([4, 5]);
+        assert_eq!(message_leaf_hash(&message), hash(&packed).to_bytes());
+    }
+}
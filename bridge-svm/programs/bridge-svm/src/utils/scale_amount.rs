@@ -3,7 +3,7 @@
 // can safely use u128 when (to_decimals - from_decimals) <= 19
 // todo add decimals assertion on token initialization?
 pub fn scale_amount(num: u64, from_decimals: u8, to_decimals: u8) -> [u8; 32] {
-    let factor = 10u128.pow((to_decimals as i32 - from_decimals as i32).abs() as u32);
+    let factor = 10u128.pow((to_decimals as i32 - from_decimals as i32).unsigned_abs());
     let mut lo = num as u128;
 
     if to_decimals > from_decimals {
@@ -27,7 +27,7 @@ mod tests {
 
     #[test]
     fn test_scale_up() {
-        let num = 1488_000_000;
+        let num = 1_488_000_000;
         let from_decimals = 6;
         let to_decimals = 18;
         let result = scale_amount(num, from_decimals, to_decimals);
@@ -3,6 +3,7 @@ use anchor_lang::context::CpiContext;
 use anchor_lang::prelude::*;
 use anchor_lang::solana_program::entrypoint::ProgramResult;
 use anchor_lang::solana_program::system_instruction;
+use anchor_spl::token::{self, spl_token::instruction::AuthorityType};
 use anchor_spl::token_interface;
 
 pub fn transfer_native_from_user<'a>(
@@ -20,29 +21,36 @@ pub fn transfer_spl_from_user<'a>(
     authority_info: AccountInfo<'a>,
     sender_ata_info: AccountInfo<'a>,
     receiver_ata_info: AccountInfo<'a>,
+    mint_info: AccountInfo<'a>,
+    decimals: u8,
     amount: u64,
     token_program_info: AccountInfo<'a>,
 ) -> Result<()> {
-    let cpi_accounts = token_interface::Transfer {
+    let cpi_accounts = token_interface::TransferChecked {
         from: sender_ata_info,
+        mint: mint_info,
         to: receiver_ata_info,
         authority: authority_info,
     };
 
     let cpi_ctx = CpiContext::new(token_program_info, cpi_accounts);
-    token_interface::transfer(cpi_ctx, amount)
+    token_interface::transfer_checked(cpi_ctx, amount, decimals)
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn transfer_spl_to_user<'a>(
     authority_info: AccountInfo<'a>,
     sender_ata_info: AccountInfo<'a>,
     receiver_ata_info: AccountInfo<'a>,
+    mint_info: AccountInfo<'a>,
+    decimals: u8,
     amount: u64,
     token_program_info: AccountInfo<'a>,
     bridge_token: TokenConfig,
 ) -> Result<()> {
-    let cpi_accounts = token_interface::Transfer {
+    let cpi_accounts = token_interface::TransferChecked {
         from: sender_ata_info,
+        mint: mint_info,
         to: receiver_ata_info,
         authority: authority_info,
     };
@@ -53,7 +61,7 @@ pub fn transfer_spl_to_user<'a>(
     ]];
 
     let cpi_ctx = CpiContext::new_with_signer(token_program_info, cpi_accounts, signer_seeds);
-    token_interface::transfer(cpi_ctx, amount)
+    token_interface::transfer_checked(cpi_ctx, amount, decimals)
 }
 
 pub fn burn_spl_from_user<'a>(
@@ -101,3 +109,25 @@ pub fn mint_spl_to_user<'a>(
     let cpi_ctx = CpiContext::new_with_signer(token_program_info, cpi_accounts, signer_seeds);
     token_interface::mint_to(cpi_ctx, amount)
 }
+
+pub fn set_mint_authority<'a>(
+    authority_info: AccountInfo<'a>,
+    mint_info: AccountInfo<'a>,
+    authority_type: AuthorityType,
+    new_authority: Pubkey,
+    token_program_info: AccountInfo<'a>,
+    bridge_token: TokenConfig,
+) -> Result<()> {
+    let cpi_accounts = token::SetAuthority {
+        current_authority: authority_info,
+        account_or_mint: mint_info,
+    };
+    let signer_seeds: &[&[&[u8]]] = &[&[
+        TokenConfig::SEED_PREFIX,
+        bridge_token.token.as_ref(),
+        &[bridge_token.bump],
+    ]];
+
+    let cpi_ctx = CpiContext::new_with_signer(token_program_info, cpi_accounts, signer_seeds);
+    token::set_authority(cpi_ctx, authority_type, Some(new_authority))
+}
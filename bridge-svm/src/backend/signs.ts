@@ -31,7 +31,10 @@ receiveSigners.forEach((signer, i) => receiveSignersBuffer.set(signer.publicKey.
 export const receiveSigner = new PublicKey(keccak_256(receiveSignersBuffer));
 
 
-export async function getReceivePayload(user: PublicKey, token: PublicKey, amountTo: number, nonce: number, eventId: number): Promise<SignedPayload<ReceivePayload>> {
+// how long a signed receive payload can be claimed, in seconds
+const RECEIVE_PAYLOAD_VALIDITY = 7 * 24 * 60 * 60;
+
+export async function getReceivePayload(user: PublicKey, token: PublicKey, amountTo: number, nonce: number, eventId: number, signerEpoch = 0): Promise<SignedPayload<ReceivePayload>> {
   // get from db
   const payload: ReceivePayload = {
     to: user.toBytes(),
@@ -40,6 +43,9 @@ export async function getReceivePayload(user: PublicKey, token: PublicKey, amoun
     chainFrom: AMB_CHAIN_ID,
     chainTo: SOLANA_CHAIN_ID,
     eventId,
+    expiresAt: Math.floor(Date.now() / 1000) + RECEIVE_PAYLOAD_VALIDITY,
+    signerEpoch,  // `signerEpoch` of the bridge state
+    source: { receiptHash: new Uint8Array(32), txHash: new Uint8Array(32) },  // todo
     flags: new Uint8Array(32),  // todo
     flagData: numberToUint8Array(Number(nonce), 8),  // todo
   };
@@ -50,7 +56,21 @@ export async function getReceivePayload(user: PublicKey, token: PublicKey, amoun
 }
 
 
+// last quote id issued to each sender; ids are millisecond timestamps, bumped past the last one
+// so two quotes in the same millisecond don't collide on the sender's `ConsumedQuote` PDA
+const lastQuoteIds = new Map<string, number>();
+
+export function nextQuoteId(sender: PublicKey): number {
+  const key = sender.toBase58();
+  const quoteId = Math.max(Date.now(), (lastQuoteIds.get(key) ?? 0) + 1);
+  lastQuoteIds.set(key, quoteId);
+  return quoteId;
+}
+
+
 export async function getSendPayload(
+  sender: PublicKey,
+  recipient: string,
   tokenAddressFrom: PublicKey,
   tokenAddressTo: string,
   amountToSend: number,
@@ -60,6 +80,8 @@ export async function getSendPayload(
   const timestamp = Math.floor(Date.now() / 1000);
 
   const payload: SendPayload = {
+    sender: sender.toBytes(),
+    recipient: hexToUint8Array(recipient),
     tokenAddressFrom: tokenAddressFrom.toBytes(),
     tokenAddressTo: hexToUint8Array(tokenAddressTo),
     amountToSend: amountToSend,
@@ -67,6 +89,7 @@ export async function getSendPayload(
     chainFrom: SOLANA_CHAIN_ID,
     chainTo: AMB_CHAIN_ID,
     timestamp,
+    quoteId: nextQuoteId(sender),
     flags: new Uint8Array(32), // todo
     flagData: new Uint8Array(0),  // todo
   };
@@ -1,6 +1,7 @@
 import { Buffer } from "buffer";
 import * as borsh from "borsh";
 import { PublicKey } from "@solana/web3.js";
+import { keccak_256 } from "@noble/hashes/sha3";
 
 
 export interface BackendSignature {
@@ -18,7 +19,7 @@ export type SignedPayload<T> = {
 export interface IBackend {
   getReceivePayload(... args: any[]): Promise<SignedPayload<ReceivePayload>>
 
-  getSendPayload(tokenAddressFrom: PublicKey, tokenAddressTo: string, amountToSend: number, flags: any): Promise<SignedPayload<SendPayload>>
+  getSendPayload(sender: PublicKey, recipient: string, tokenAddressFrom: PublicKey, tokenAddressTo: string, amountToSend: number, flags: any): Promise<SignedPayload<SendPayload>>
 }
 
 const _b20 = { array: { type: 'u8', len: 20 } };
@@ -27,6 +28,8 @@ const serialize = (value: any, schema: any) => Buffer.from(borsh.serialize({ str
 
 
 export interface SendPayload {
+  sender: Uint8Array;
+  recipient: Uint8Array;
   tokenAddressFrom: Uint8Array;
   tokenAddressTo: Uint8Array;
   amountToSend: number | bigint;
@@ -34,11 +37,14 @@ export interface SendPayload {
   chainFrom: number | bigint;
   chainTo: number | bigint;
   timestamp: number | bigint;
+  quoteId: number | bigint;
   flags: Uint8Array;
   flagData: Uint8Array;
 }
 
 const sendSchema = {
+  sender: _b32,
+  recipient: _b20,
   tokenAddressFrom: _b32,
   tokenAddressTo: _b20,
   amountToSend: 'u64',
@@ -46,10 +52,158 @@ const sendSchema = {
   chainFrom: 'u64',
   chainTo: 'u64',
   timestamp: 'u64',
+  quoteId: 'u64',
   flags: _b32,
   flagData: { array: { type: 'u8' } },
 }
 
+export interface SendLeg {
+  tokenAddressFrom: Uint8Array;
+  tokenAddressTo: Uint8Array;
+  amountToSend: number | bigint;
+  flags: Uint8Array;
+  flagData: Uint8Array;
+}
+
+export interface SendBatchPayload {
+  sender: Uint8Array;
+  recipient: Uint8Array;
+  legs: SendLeg[];
+  feeAmount: number | bigint;
+  chainFrom: number | bigint;
+  chainTo: number | bigint;
+  timestamp: number | bigint;
+  quoteId: number | bigint;
+}
+
+const sendLegSchema = {
+  struct: {
+    tokenAddressFrom: _b32,
+    tokenAddressTo: _b20,
+    amountToSend: 'u64',
+    flags: _b32,
+    flagData: { array: { type: 'u8' } },
+  }
+}
+
+const sendBatchSchema = {
+  sender: _b32,
+  recipient: _b20,
+  legs: { array: { type: sendLegSchema } },
+  feeAmount: 'u64',
+  chainFrom: 'u64',
+  chainTo: 'u64',
+  timestamp: 'u64',
+  quoteId: 'u64',
+}
+
+export interface SendMessagePayload {
+  sender: Uint8Array;
+  target: Uint8Array;
+  payload: Uint8Array;
+  feeAmount: number | bigint;
+  chainFrom: number | bigint;
+  chainTo: number | bigint;
+  timestamp: number | bigint;
+  quoteId: number | bigint;
+}
+
+const sendMessageSchema = {
+  sender: _b32,
+  target: _b20,
+  payload: { array: { type: 'u8' } },
+  feeAmount: 'u64',
+  chainFrom: 'u64',
+  chainTo: 'u64',
+  timestamp: 'u64',
+  quoteId: 'u64',
+}
+
+export interface ReceiveMessagePayload {
+  sender: Uint8Array;
+  target: Uint8Array;
+  chainFrom: number | bigint;
+  chainTo: number | bigint;
+  eventId: number | bigint;
+  expiresAt: number | bigint;
+  signerEpoch: number | bigint;
+  payload: Uint8Array;
+}
+
+const receiveMessageSchema = {
+  sender: _b20,
+  target: _b32,
+  chainFrom: 'u64',
+  chainTo: 'u64',
+  eventId: 'u64',
+  expiresAt: 'u64',
+  signerEpoch: 'u64',
+  payload: { array: { type: 'u8' } },
+}
+
+export interface RefundPayload {
+  eventId: number | bigint;
+  sender: Uint8Array;
+  tokenAddress: Uint8Array;
+  amount: number | bigint;
+  chainFrom: number | bigint;
+  chainTo: number | bigint;
+}
+
+const refundSchema = {
+  eventId: 'u64',
+  sender: _b32,
+  tokenAddress: _b32,
+  amount: 'u64',
+  chainFrom: 'u64',
+  chainTo: 'u64',
+}
+
+export interface SkipNoncePayload {
+  receiver: Uint8Array;
+  currentNonce: number | bigint;
+  newNonce: number | bigint;
+  voidedEventIds: (number | bigint)[];
+  chainFrom: number | bigint;
+  chainTo: number | bigint;
+}
+
+const skipNonceSchema = {
+  receiver: _b32,
+  currentNonce: 'u64',
+  newNonce: 'u64',
+  voidedEventIds: { array: { type: 'u64' } },
+  chainFrom: 'u64',
+  chainTo: 'u64',
+}
+
+export interface ReceiptClaimedPayload {
+  eventId: number | bigint;
+  receiptHash: Uint8Array;
+  chainFrom: number | bigint;
+  chainTo: number | bigint;
+}
+
+const receiptClaimedSchema = {
+  eventId: 'u64',
+  receiptHash: _b32,
+  chainFrom: 'u64',
+  chainTo: 'u64',
+}
+
+// the AMB transfer a receive comes from
+export interface SourceRef {
+  receiptHash: Uint8Array;  // `ReceiptUtils.toHash` of the AMB receipt
+  txHash: Uint8Array;  // AMB transaction that emitted the receipt
+}
+
+const sourceRefSchema = {
+  struct: {
+    receiptHash: _b32,
+    txHash: _b32,
+  }
+}
+
 export interface ReceivePayload {
   to: Uint8Array;
   tokenAddressTo: Uint8Array;
@@ -57,6 +211,9 @@ export interface ReceivePayload {
   chainFrom: number | bigint;
   chainTo: number | bigint;
   eventId: number | bigint;
+  expiresAt: number | bigint;
+  signerEpoch: number | bigint;
+  source: SourceRef;
   flags: Uint8Array;
   flagData: Uint8Array;
 }
@@ -68,10 +225,24 @@ const receiveSchema = {
   chainFrom: 'u64',
   chainTo: 'u64',
   eventId: 'u64',
+  expiresAt: 'u64',
+  signerEpoch: 'u64',
+  source: sourceRefSchema,
   flags: _b32,
   flagData: { array: { type: 'u8' } },
 }
 
 
 export const serializeSendPayload = (value: SendPayload) => serialize(value, sendSchema);
-export const serializeReceivePayload = (value: ReceivePayload) => serialize(value, receiveSchema)
+export const serializeSendBatchPayload = (value: SendBatchPayload) => serialize(value, sendBatchSchema);
+// payloads signed by the receive signers start with the tag of their type (`ReceiveSigned::TAG`)
+const tagged = (tag: string, payload: Buffer) => Buffer.concat([Buffer.from(tag), payload]);
+export const serializeReceivePayload = (value: ReceivePayload) => tagged('amb:recv', serialize(value, receiveSchema))
+// message signed for `receive_batch`: its tag and the concatenated hashes of the legs' payloads
+export const serializeReceiveBatchPayloads = (values: ReceivePayload[]) =>
+  tagged('amb:rbat', Buffer.concat(values.map((value) => keccak_256(serializeReceivePayload(value)))));
+export const serializeSendMessagePayload = (value: SendMessagePayload) => serialize(value, sendMessageSchema);
+export const serializeReceiveMessagePayload = (value: ReceiveMessagePayload) => tagged('amb:rmsg', serialize(value, receiveMessageSchema));
+export const serializeRefundPayload = (value: RefundPayload) => tagged('amb:rfnd', serialize(value, refundSchema));
+export const serializeSkipNoncePayload = (value: SkipNoncePayload) => tagged('amb:skip', serialize(value, skipNonceSchema));
+export const serializeReceiptClaimedPayload = (value: ReceiptClaimedPayload) => tagged('amb:rclm', serialize(value, receiptClaimedSchema));
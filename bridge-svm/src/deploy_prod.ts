@@ -1,16 +1,16 @@
 import { clusterApiUrl, Connection, Keypair, PublicKey } from "@solana/web3.js";
-import { AnchorProvider, Program, setProvider, } from "@coral-xyz/anchor";
+import { AnchorProvider, BN, Program, setProvider, } from "@coral-xyz/anchor";
 import type { AmbSolBridge } from "./idl/idlType";
 import idl from "./idl/idl.json";
 
-import { getBridgeStateAccount, initializeToken, } from "./sdk/utils";
+import { getBridgeStateAccount, initializeToken, queueOperation, } from "./sdk/utils";
 import { Buffer } from "buffer";
 import { keccak_256 } from "@noble/hashes/sha3";
 import NodeWallet from "@coral-xyz/anchor/dist/esm/nodewallet";
 import { NATIVE_MINT } from "@solana/spl-token";
 
 
-const adminKeypairPK = []
+const adminKeypairPK = []  // must be the program upgrade authority to initialize
 const admin = Keypair.fromSecretKey(new Uint8Array(adminKeypairPK));
 
 
@@ -22,6 +22,8 @@ const receiveSigners = [
 ].sort((a, b) => a.toBase58().localeCompare(b.toBase58()));
 const sendSigner = new PublicKey("FMYR5BFh3JapZS1cfwYViiBMYJxFGwKdchnghBnBtxkk");  // svin
 
+const timelockDelay = new BN(2 * 24 * 60 * 60);  // 2 days
+
 const receiveSignersBuffer = Buffer.alloc(32 * receiveSigners.length);
 receiveSigners.forEach((signer, i) => receiveSignersBuffer.set(signer.toBuffer(), i * 32));
 const receiveSigner = new PublicKey(keccak_256(receiveSignersBuffer));
@@ -64,25 +66,21 @@ export async function main() {
 
 
 async function setSigners(sendSigner: PublicKey, receiveSigner: PublicKey) {
-  await program.methods
-    .setSigners(sendSigner, receiveSigner)
-    .accounts({
-      admin: admin.publicKey,
-    })
-    .signers([admin])
-    .rpc();
+  // takes effect after the timelock delay, via `executeOperation`
+  await queueOperation(program, admin, { setSigners: { sendSigner, receiveSigner } });
 }
 
 
 async function initialize(sendSigner: PublicKey, receiveSigner: PublicKey) {
   console.log("sendSigner", sendSigner.toBase58());
   console.log("receiveSigner", receiveSigner.toString());
-  await program.methods.initialize(sendSigner, receiveSigner)
+  await program.methods.initialize(sendSigner, receiveSigner, timelockDelay)
     .accounts({
       admin: admin.publicKey,
     })
     .signers([admin])
     .rpc();
+  await program.methods.initSendAccumulator().accounts({ admin: admin.publicKey }).signers([admin]).rpc();
 }
 
 
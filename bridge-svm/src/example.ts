@@ -1,6 +1,7 @@
 import { clusterApiUrl, Connection, Keypair, PublicKey } from "@solana/web3.js";
 import {
   AnchorProvider,
+  BN,
   BorshCoder,
   EventParser,
   Program,
@@ -14,7 +15,7 @@ import {
   getBridgeStateAccount,
   getBridgeTokenAccounts,
   getOrCreateUserATA,
-  getUserNoncePda, getUserNonceValue, initializeToken,
+  getUserNoncePda, getUserNonceValue, initializeToken, queueOperation,
 } from "./sdk/utils";
 import { createMint, mintTo, NATIVE_MINT } from "@solana/spl-token";
 import { Buffer } from "buffer";
@@ -94,13 +95,8 @@ export async function main() {
 
 
 async function setSigners(sendSigner: PublicKey, receiveSigner: PublicKey) {
-  await program.methods
-    .setSigners(sendSigner, receiveSigner)
-    .accounts({
-      admin: admin.publicKey,
-    })
-    .signers([admin])
-    .rpc();
+  // takes effect after the timelock delay, via `executeOperation`
+  await queueOperation(program, admin, { setSigners: { sendSigner, receiveSigner } });
 }
 
 
@@ -172,12 +168,13 @@ async function initialize() {
   const receiveSigner = new PublicKey(keccak_256(receiveSignersBuffer));
 
   await program.methods
-    .initialize(sendSigner.publicKey, receiveSigner)
+    .initialize(sendSigner.publicKey, receiveSigner, new BN(0))  // no timelock delay for the example
     .accounts({
       admin: admin.publicKey,
     })
     .signers([admin])
     .rpc();
+  await program.methods.initSendAccumulator().accounts({ admin: admin.publicKey }).signers([admin]).rpc();
 }
 
 /**
@@ -3,13 +3,13 @@ import { mplToolbox } from "@metaplex-foundation/mpl-toolbox";
 import { createSignerFromKeypair, percentAmount, publicKey, signerIdentity } from "@metaplex-foundation/umi";
 import { createUmi } from "@metaplex-foundation/umi-bundle-defaults";
 import { base58 } from "@metaplex-foundation/umi/serializers";
-import { clusterApiUrl, Connection, Keypair } from "@solana/web3.js";
+import { clusterApiUrl, Connection, Keypair, PublicKey } from "@solana/web3.js";
 import { AuthorityType, createMint, setAuthority } from "@solana/spl-token";
 import NodeWallet from "@coral-xyz/anchor/dist/esm/nodewallet";
 import { AnchorProvider, Program, setProvider } from "@coral-xyz/anchor";
 import idl from "./idl/idl.json";
 import type { AmbSolBridge } from "./idl/idlType";
-import { getBridgeTokenAccounts } from "./sdk/utils";
+import { getBridgeTokenAccounts, queueOperation } from "./sdk/utils";
 
 import sambKeypairPK from "../SAMBiNFocuZgLqkGHZbe2u6gugF861MdMkgrDdiuEpW.json";
 
@@ -32,12 +32,12 @@ async function deploy() {
   await createMint(connection, admin, admin.publicKey, undefined, 9, sambKeypair);
 }
 
-async function changeAuthorityFromBridge() {
-  await program.methods.changeMintAuthority(admin.publicKey).accounts({
-    mint: sambKeypair.publicKey,
-  })
-    .signers([admin])
-    .rpc();
+async function migrateToSuccessorBridge(successorProgram: PublicKey) {
+  // takes effect after the timelock delay, via `executeOperation`;
+  // mint authority goes to the successor's token PDA, metadata update authority must be moved separately
+  await queueOperation(program, admin, {
+    migrateToken: { mint: sambKeypair.publicKey, successorProgram }
+  });
 }
 
 
@@ -103,7 +103,7 @@ async function changeAuthority() {
 }
 
 async function main() {
-  // await changeAuthorityFromBridge()
+  // await migrateToSuccessorBridge(new PublicKey(""))
   // await deploy()
   // await metadata()
   // await changeAuthority()
@@ -27,6 +27,8 @@ export async function receive(
     .receive(
       new BN(payload.amountTo.toString()),
       new BN(payload.eventId.toString()),
+      new BN(payload.expiresAt.toString()),
+      { receiptHash: [...payload.source.receiptHash], txHash: [...payload.source.txHash] },
       [...payload.flags],
       Buffer.from(payload.flagData)
     ).accountsPartial({
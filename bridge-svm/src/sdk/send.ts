@@ -1,6 +1,6 @@
-import { getBridgeTokenInfo, hexToUint8Array } from "./utils";
+import { getBridgeTokenInfo } from "./utils";
 import { Connection, PublicKey, sendAndConfirmTransaction, type Signer, Transaction } from "@solana/web3.js";
-import { Program } from "@coral-xyz/anchor";
+import { BN, Program } from "@coral-xyz/anchor";
 import { verifySignatureInstruction } from "./ed25519_ix";
 import type { AmbSolBridge } from "../idl/idlType";
 import { wrapSolInstructions } from "./wsol_ix";
@@ -17,9 +17,11 @@ export async function send(
   bridgeProgram: Program<AmbSolBridge>,
   amountToSend: number,
   flags: any,  // todo
-  backend: IBackend
+  backend: IBackend,
+  maxFee?: number,  // max fee the user agreed to; defaults to the quoted fee
+  deadline = Math.floor(Date.now() / 1000) + 5 * 60,  // unix timestamp after which the transaction fails
 ) {
-  const { serializedPayload, signature } = await backend.getSendPayload(tokenFrom, tokenTo, amountToSend, flags);
+  const { payload, serializedPayload, signature } = await backend.getSendPayload(userFrom.publicKey, userTo, tokenFrom, tokenTo, amountToSend, flags);
   const verifyInstruction = verifySignatureInstruction(signature);
 
   const { isMintable } = await getBridgeTokenInfo(bridgeProgram, tokenFrom);
@@ -28,7 +30,7 @@ export async function send(
     await wrapSolInstructions(connection, userFrom, amountToSend) : [];
 
   const sendInstruction = await bridgeProgram.methods
-    .send(serializedPayload, [...hexToUint8Array(userTo)])
+    .send(serializedPayload, new BN(payload.quoteId.toString()), new BN((maxFee ?? payload.feeAmount).toString()), new BN(deadline))
     .accountsPartial({
       sender: userFrom.publicKey,
       mint: tokenFrom,
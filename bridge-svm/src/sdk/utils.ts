@@ -1,7 +1,7 @@
 import { Buffer } from "buffer";
-import { Connection, Keypair, PublicKey, type Signer } from "@solana/web3.js";
-import { getAssociatedTokenAddressSync, getOrCreateAssociatedTokenAccount, } from "@solana/spl-token";
-import { Program } from "@coral-xyz/anchor";
+import { type AccountMeta, Connection, Keypair, PublicKey, type Signer } from "@solana/web3.js";
+import { getAssociatedTokenAddressSync, getOrCreateAssociatedTokenAccount, TOKEN_PROGRAM_ID, } from "@solana/spl-token";
+import { BN, IdlTypes, Program } from "@coral-xyz/anchor";
 import type { AmbSolBridge } from "../idl/idlType";
 import { HDKey } from "micro-key-producer/slip10.js";
 import * as bip39 from "bip39";
@@ -67,6 +67,47 @@ export function getUserNoncePda(user: PublicKey, bridgeProgramId: PublicKey) {
   return nonceAccount;
 }
 
+export function getRolesPda(member: PublicKey, bridgeProgramId: PublicKey) {
+  const [rolesAccount] = PublicKey.findProgramAddressSync([Buffer.from("roles"), member.toBuffer()], bridgeProgramId);
+  return rolesAccount;
+}
+
+export function getQueuedOperationPda(id: BN, bridgeProgramId: PublicKey) {
+  const [queuedOperation] = PublicKey.findProgramAddressSync([Buffer.from("timelock"), id.toArrayLike(Buffer, "le", 8)], bridgeProgramId);
+  return queuedOperation;
+}
+
+export function getConsumedQuotePda(sender: PublicKey, quoteId: BN, bridgeProgramId: PublicKey) {
+  const [consumedQuote] = PublicKey.findProgramAddressSync([Buffer.from("quote"), sender.toBuffer(), quoteId.toArrayLike(Buffer, "le", 8)], bridgeProgramId);
+  return consumedQuote;
+}
+
+export function getRefundedSendPda(eventId: BN, bridgeProgramId: PublicKey) {
+  const [refundedSend] = PublicKey.findProgramAddressSync([Buffer.from("refund"), eventId.toArrayLike(Buffer, "le", 8)], bridgeProgramId);
+  return refundedSend;
+}
+
+export function getSendAccumulatorPda(bridgeProgramId: PublicKey) {
+  const [accumulator] = PublicKey.findProgramAddressSync([Buffer.from("send_accumulator")], bridgeProgramId);
+  return accumulator;
+}
+
+export function getSendReceiptPda(eventId: BN, bridgeProgramId: PublicKey) {
+  const [receipt] = PublicKey.findProgramAddressSync([Buffer.from("receipt"), eventId.toArrayLike(Buffer, "le", 8)], bridgeProgramId);
+  return receipt;
+}
+
+export function getReceivedMessagePda(eventId: BN, bridgeProgramId: PublicKey) {
+  const [receivedMessage] = PublicKey.findProgramAddressSync([Buffer.from("message"), eventId.toArrayLike(Buffer, "le", 8)], bridgeProgramId);
+  return receivedMessage;
+}
+
+// signs `receive_and_call` CPIs of `receiver` and owns its escrows
+export function getCallAuthority(receiver: PublicKey, bridgeProgramId: PublicKey) {
+  const [callAuthority] = PublicKey.findProgramAddressSync([Buffer.from("call_authority"), receiver.toBuffer()], bridgeProgramId);
+  return callAuthority;
+}
+
 export function getBridgeStateAccount(bridgeProgramId: PublicKey) {
   const [state_pda] = PublicKey.findProgramAddressSync([Buffer.from("global_state")], bridgeProgramId);
   return state_pda
@@ -79,25 +120,131 @@ export function getBridgeTokenAccounts(token: PublicKey, bridgeProgramId: Public
 }
 
 
-export async function initializeToken(bridgeProgram: Program<AmbSolBridge>, admin: Keypair, tokenPublicKey: PublicKey, ambAddress: string, ambDecimals = 18, isSynthetic = false) {
-  await bridgeProgram.methods.initializeToken([...hexToUint8Array(ambAddress)], ambDecimals, isSynthetic).accountsPartial({
-    admin: admin.publicKey,
+// remaining accounts of `sendBatch`, 4 per leg
+// with `withReceipts`, each leg also gets a `SendReceipt` at the event id it will take
+export async function getSendBatchAccounts(bridgeProgram: Program<AmbSolBridge>, sender: PublicKey, mints: PublicKey[], withReceipts = false) {
+  const accounts: AccountMeta[] = [];
+  const { nonce } = await bridgeProgram.account.globalState.fetch(getBridgeStateAccount(bridgeProgram.programId));
+  for (const [i, mint] of mints.entries()) {
+    const [bridgeToken, bridgeTokenAccount] = getBridgeTokenAccounts(mint, bridgeProgram.programId);
+    const { isMintable } = await getBridgeTokenInfo(bridgeProgram, mint);
+    accounts.push(
+      { pubkey: mint, isSigner: false, isWritable: true },
+      { pubkey: bridgeToken, isSigner: false, isWritable: false },
+      { pubkey: getAssociatedTokenAddressSync(mint, sender), isSigner: false, isWritable: true },
+      // bridge program id instead of bridge token account for mintable tokens
+      { pubkey: isMintable ? bridgeProgram.programId : bridgeTokenAccount, isSigner: false, isWritable: !isMintable },
+    );
+    if (withReceipts)
+      accounts.push({ pubkey: getSendReceiptPda(nonce.addn(i + 1), bridgeProgram.programId), isSigner: false, isWritable: true });
+  }
+  return accounts;
+}
+
+export async function getReceiveBatchAccounts(bridgeProgram: Program<AmbSolBridge>, receiver: PublicKey, mints: PublicKey[]) {
+  const accounts: AccountMeta[] = [];
+  for (const mint of mints) {
+    const [bridgeToken, bridgeTokenAccount] = getBridgeTokenAccounts(mint, bridgeProgram.programId);
+    const { isMintable } = await getBridgeTokenInfo(bridgeProgram, mint);
+    accounts.push(
+      { pubkey: mint, isSigner: false, isWritable: true },
+      { pubkey: bridgeToken, isSigner: false, isWritable: false },
+      // receiver token account must already exist
+      { pubkey: getAssociatedTokenAddressSync(mint, receiver), isSigner: false, isWritable: true },
+      // bridge program id instead of bridge token account for mintable tokens
+      { pubkey: isMintable ? bridgeProgram.programId : bridgeTokenAccount, isSigner: false, isWritable: !isMintable },
+    );
+  }
+  return accounts;
+}
+
+export async function initializeToken(bridgeProgram: Program<AmbSolBridge>, authority: Keypair, tokenPublicKey: PublicKey, ambAddress: string, ambDecimals = 18, isSynthetic = false, authorityRoles: PublicKey | null = null) {
+  return await bridgeProgram.methods.initializeToken([...hexToUint8Array(ambAddress)], ambDecimals, isSynthetic).accountsPartial({
+    authority: authority.publicKey,
+    authorityRoles,  // null for super-admin, roles PDA for token managers
     mint: tokenPublicKey,
     bridgeTokenAccount: isSynthetic ? null : undefined  // empty value (null) for synthetic, auto-resoluted for non-synthetic
-  }).signers([admin]).rpc();
+  }).signers([authority]).rpc({ commitment: "confirmed" });
+}
+
+
+export type TimelockOperation = IdlTypes<AmbSolBridge>["timelockOperation"];
+
+export async function queueOperation(bridgeProgram: Program<AmbSolBridge>, proposer: Keypair, operation: TimelockOperation, proposerRoles: PublicKey | null = null) {
+  const state = await bridgeProgram.account.globalState.fetch(getBridgeStateAccount(bridgeProgram.programId));
+  const queuedOperation = getQueuedOperationPda(state.timelockNonce, bridgeProgram.programId);
+  await bridgeProgram.methods.queueOperation(operation).accountsPartial({
+    proposer: proposer.publicKey,
+    proposerRoles,  // null for super-admin, roles PDA for role members
+    queuedOperation,
+  }).signers([proposer]).rpc();
+  return queuedOperation;
 }
 
+// anyone can execute operation after the timelock delay; only the accounts used by the operation are needed
+export async function executeOperation(bridgeProgram: Program<AmbSolBridge>, queuedOperation: PublicKey) {
+  const { operation, proposer } = await bridgeProgram.account.queuedOperation.fetch(queuedOperation);
+  const mint = operation.migrateToken?.mint ?? null;
+  return bridgeProgram.methods.executeOperation().accountsPartial({
+    queuedOperation,
+    proposer,
+    receiver: operation.withdrawFees?.receiver ?? null,
+    mint,
+    bridgeToken: mint ? getBridgeTokenAccounts(mint, bridgeProgram.programId)[0] : null,
+    tokenProgram: mint ? TOKEN_PROGRAM_ID : null,
+  }).rpc({ commitment: "confirmed" });
+}
 
+
+export enum PauseFlags {
+  SEND = 1 << 0,
+  RECEIVE = 1 << 1,
+  TOKEN_REGISTRATION = 1 << 2,
+  FEE_WITHDRAWAL = 1 << 3,
+  ALL = SEND | RECEIVE | TOKEN_REGISTRATION | FEE_WITHDRAWAL,
+}
+
+// bits of the 256-bit big-endian flags, same as EVM `BridgeFlags`
 export enum Flags {
-  SHOULD_UNWRAP = 1
+  SHOULD_UNWRAP = 65,
+  SEND_NATIVE_TO_RECEIVER = 66,
 }
 
 export function checkFlags(flags: Uint8Array, flag: Flags) {
   return getBit(flags, flag) === 1;
 }
 
+export function makeFlags(...flags: Flags[]) {
+  const bytes = new Uint8Array(32);
+  for (const flag of flags)
+    bytes[31 - Math.floor(flag / 8)] |= 1 << (flag % 8);
+  return bytes;
+}
+
 function getBit(arr: Uint8Array, bitIndex: number): number {
-  const byteIndex = Math.floor(bitIndex / 8);
+  const byteIndex = arr.length - 1 - Math.floor(bitIndex / 8);
   const bitPosition = bitIndex % 8;
   return (arr[byteIndex] >> bitPosition) & 1;
 }
+
+// Anchor's tag of self-CPI event instructions (`EVENT_IX_TAG_LE`), followed by the event discriminator and data
+export const EVENT_IX_TAG_LE = Buffer.from([0xe4, 0x45, 0xa5, 0x2e, 0x51, 0xcb, 0x9a, 0x1d]);
+
+export enum FlagDataTag {
+  CALL = 1,
+  TO_TOKEN_ACCOUNT = 2,  // empty value, `to` is a token account instead of an owner
+}
+
+// version byte, then tag (u8), length (u16 little-endian) and value of each entry
+export function encodeFlagData(entries: [FlagDataTag, Uint8Array][]) {
+  if (entries.length === 0) return Buffer.alloc(0);
+  return Buffer.concat([
+    Buffer.from([1]),
+    ...entries.map(([tag, value]) => {
+      const header = Buffer.alloc(3);
+      header.writeUInt8(tag, 0);
+      header.writeUInt16LE(value.length, 1);
+      return Buffer.concat([header, value]);
+    }),
+  ]);
+}
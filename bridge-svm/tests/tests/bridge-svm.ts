@@ -1,33 +1,74 @@
 import { Buffer } from "buffer";
 
-import { AnchorProvider, BN, BorshCoder, EventParser, Program, setProvider, workspace } from "@coral-xyz/anchor";
+import { AnchorProvider, BN, BorshCoder, EventParser, Program, setProvider, utils, workspace } from "@coral-xyz/anchor";
 import {
+  ComputeBudgetProgram,
   Keypair,
   ParsedAccountData,
   PublicKey,
   sendAndConfirmTransaction,
+  SYSVAR_INSTRUCTIONS_PUBKEY,
+  SystemProgram,
   Transaction,
   TransactionSignature
 } from '@solana/web3.js';
 
-import { createMint, getAssociatedTokenAddressSync, mintTo, NATIVE_MINT } from "@solana/spl-token";
+import { approve, createAccount, createMint, getAssociatedTokenAddressSync, getMint, mintTo, NATIVE_MINT, TOKEN_PROGRAM_ID, transfer } from "@solana/spl-token";
 
 import { AmbSolBridge } from "../../target/types/amb_sol_bridge";
-import { receiveSigner, receiveSigners, sendSigner, signMessage } from "../../src/backend/signs";
+import { BridgeIntegrator } from "../../target/types/bridge_integrator";
+import { getSendPayload, receiveSigner, receiveSigners, sendSigner, signMessage } from "../../src/backend/signs";
 import {
   AMB_CHAIN_ID,
+  encodeFlagData,
+  EVENT_IX_TAG_LE,
+  executeOperation,
+  FlagDataTag,
+  Flags,
   getBridgeStateAccount,
   getBridgeTokenAccounts,
+  getCallAuthority,
+  getConsumedQuotePda,
   getOrCreateUserATA,
+  getReceivedMessagePda,
+  getReceiveBatchAccounts,
+  getRefundedSendPda,
+  getSendAccumulatorPda,
+  getSendBatchAccounts,
+  getSendReceiptPda,
+  getRolesPda,
   getUserNoncePda,
   hexToUint8Array,
   initializeToken,
+  makeFlags,
   numberToUint8Array,
+  PauseFlags,
+  queueOperation,
   SOLANA_CHAIN_ID
 } from "../../src/sdk/utils";
-import { ReceivePayload, SendPayload, serializeReceivePayload, serializeSendPayload } from "../../src/backend/types";
+import {
+  ReceiveMessagePayload,
+  ReceiptClaimedPayload,
+  ReceivePayload,
+  RefundPayload,
+  SourceRef,
+  SendBatchPayload,
+  SendMessagePayload,
+  SendPayload,
+  serializeReceiptClaimedPayload,
+  serializeReceiveMessagePayload,
+  serializeReceiveBatchPayloads,
+  serializeReceivePayload,
+  serializeRefundPayload,
+  serializeSendBatchPayload,
+  serializeSendMessagePayload,
+  serializeSendPayload,
+  serializeSkipNoncePayload,
+  SkipNoncePayload
+} from "../../src/backend/types";
 import { verifySignatureInstruction } from "../../src/sdk/ed25519_ix";
 
+import { keccak_256 } from "@noble/hashes/sha3";
 import { expect, use } from "chai";
 import chaiAsPromised from 'chai-as-promised';
 import { unwrapWSolInstruction, wrapSolInstructions } from "../../src/sdk/wsol_ix";
@@ -41,9 +82,11 @@ describe("my-project", () => {
 
   const program = workspace.AmbSolBridge as Program<AmbSolBridge>;
   const bridgeProgram = program;
+  const integratorProgram = workspace.BridgeIntegrator as Program<BridgeIntegrator>;
   const connection = program.provider.connection;
 
-  const admin = Keypair.generate();
+  // must be the upgrade authority to initialize the bridge
+  const admin = (program.provider as AnchorProvider).wallet.payer;
   const user = Keypair.generate();
 
   // pda - account to store some data
@@ -77,28 +120,45 @@ describe("my-project", () => {
 
 
   it("initializing global state", async () => {
-    await program.methods.initialize(sendSigner.publicKey, receiveSigner).accounts({ admin: admin.publicKey, }).signers([admin]).rpc();
+    await expect(
+      program.methods.initialize(sendSigner.publicKey, receiveSigner, new BN(0)).accounts({ admin: user.publicKey, }).signers([user]).rpc()
+    ).to.be.rejectedWith("Not the program upgrade authority");
+
+    const initSignature = await program.methods.initialize(sendSigner.publicKey, receiveSigner, new BN(0)).accounts({ admin: admin.publicKey, }).signers([admin]).rpc({ commitment: 'confirmed' });
+    const [initEvent] = await getEvents(initSignature);
+    expect(initEvent.name).to.eq('stateInitialized');
+    expect(initEvent.data.admin.equals(admin.publicKey)).to.eq(true);
 
     const globalState = await program.account.globalState.fetch(getBridgeStateAccount(program.programId));
     expect(+globalState.nonce).to.eq(0);
     expect(globalState.admin.equals(admin.publicKey));
     expect(globalState.sendSigner.equals(sendSigner.publicKey));
     expect(globalState.receiveSigner.equals(receiveSigner));
-    expect(globalState.pause).to.eq(false);
+    expect(globalState.pauseFlags).to.eq(0);
 
+    await expect(
+      program.methods.initSendAccumulator().accounts({ admin: user.publicKey }).signers([user]).rpc()
+    ).to.be.rejectedWith("Not an admin");
+    const accumulatorSignature = await program.methods.initSendAccumulator().accounts({ admin: admin.publicKey }).signers([admin]).rpc({ commitment: 'confirmed' });
+    const accumulator = await program.account.sendAccumulator.fetch(getSendAccumulatorPda(program.programId));
+    expect(+accumulator.firstEventId).to.eq(1);
+    expect(+accumulator.count).to.eq(0);
+    const [accumulatorEvent] = await getEvents(accumulatorSignature);
+    expect(accumulatorEvent.name).to.eq('sendAccumulatorInitialized');
+    expect(+accumulatorEvent.data.firstEventId).to.eq(1);
   });
 
 
   it("initializing global state second time - should fail", async () => {
     await expect(
-      program.methods.initialize(sendSigner.publicKey, receiveSigner)
+      program.methods.initialize(sendSigner.publicKey, receiveSigner, new BN(0))
         .accounts({ admin: admin.publicKey, }).signers([admin]).rpc()
     ).to.be.rejectedWith("already in use");
 
     // also try with another state account - should fail
     const someAccount = Keypair.generate();
     await expect(
-      program.methods.initialize(sendSigner.publicKey, receiveSigner)
+      program.methods.initialize(sendSigner.publicKey, receiveSigner, new BN(0))
         .accountsPartial({ admin: admin.publicKey, state: someAccount.publicKey }).signers([admin]).rpc()
     ).to.be.rejectedWith("A seeds constraint was violated.");
 
@@ -174,12 +234,75 @@ describe("my-project", () => {
 
     expect(after.token.user).to.eq(before.token.user - 50);
     expect(after.token.bridge).to.eq(before.token.bridge + 50);
-    expect(after.native.user).to.eq(before.native.user - 20);
+    expect(after.native.user).to.eq(before.native.user - 20 - await getQuoteRent());  // fee + rent for consumed quote
     expect(after.native.bridge).to.eq(before.native.bridge + 20);
     expect(after.sendNonce).to.eq(before.sendNonce + 1);
   });
 
 
+  it('send quote can be used once, only by its sender', async () => {
+    const quoteId = nextQuoteId();
+    // quote expires in a few seconds, so it can be closed at the end of the test
+    const timestamp = Math.floor(Date.now() / 1000) - 30 * 60 + 15;
+    const send = () => commonSend(user, tokenMint1.publicKey, ambUserAddress, ambTokenAddress1, 50, false, [], 20, quoteId, timestamp);
+
+    // quote signed for `user` can't be used by another sender
+    await getOrCreateUserATA(connection, admin, tokenMint1.publicKey);
+    const value: SendPayload = {
+      sender: user.publicKey.toBytes(),
+      recipient: ambUserAddress,
+      tokenAddressFrom: tokenMint1.publicKey.toBytes(),
+      tokenAddressTo: ambTokenAddress1,
+      amountToSend: 50,
+      feeAmount: 20,
+      chainFrom: SOLANA_CHAIN_ID,
+      chainTo: AMB_CHAIN_ID,
+      timestamp,
+      quoteId,
+      flags: new Uint8Array(32),
+      flagData: new Uint8Array(0),
+    };
+    const payload = serializeSendPayload(value);
+    const sendInstruction = await bridgeProgram.methods.send(payload, new BN(quoteId), new BN(20), new BN(timestamp + 60)).accountsPartial({
+      sender: admin.publicKey,
+      mint: tokenMint1.publicKey,
+    }).instruction();
+    const tx = new Transaction().add(verifySignatureInstruction(signMessage(payload, [sendSigner])), sendInstruction);
+    await expect(sendAndConfirmTransaction(connection, tx, [admin], { commitment: 'confirmed' })).to.be.rejectedWith("Invalid input arguments");
+
+    await send();
+    await expect(send()).to.be.rejectedWith("already in use");
+
+    const consumedQuote = getConsumedQuotePda(user.publicKey, new BN(quoteId), program.programId);
+    const closeQuote = () => bridgeProgram.methods.closeQuote().accountsPartial({ consumedQuote, sender: user.publicKey }).rpc();
+    await expect(closeQuote()).to.be.rejectedWith("Quote has not expired yet");
+
+    const expiresAt = timestamp + 30 * 60;
+    await new Promise((resolve) => setTimeout(resolve, (expiresAt + 1) * 1000 - Date.now()));
+    await closeQuote();
+    expect(await connection.getAccountInfo(consumedQuote)).to.eq(null);
+  });
+
+
+  it('backend quote ids are unique per sender', async () => {
+    const quoteIds = await Promise.all([...Array(5)].map(() =>
+      getSendPayload(user.publicKey, ambUserAddress_, tokenMint1.publicKey, ambTokenAddress1_, 50, null)
+        .then(({ payload }) => payload.quoteId)));
+    expect(new Set(quoteIds).size).to.eq(quoteIds.length);
+  });
+
+
+  it('send with user max fee and deadline', async () => {
+    const send = (maxFee: number, deadline: number) =>
+      commonSend(user, tokenMint1.publicKey, ambUserAddress, ambTokenAddress1, 50, false, [], 20, nextQuoteId(), undefined, maxFee, deadline);
+    const now = Math.floor(Date.now() / 1000);
+
+    await expect(send(19, now + 60)).to.be.rejectedWith("Fee exceeds max fee");
+    await expect(send(20, now - 60)).to.be.rejectedWith("Deadline exceeded");
+    await send(25, now + 60);
+  });
+
+
   it('receive non mintable token', async () => {
 
     const tokenTo = tokenMint1.publicKey;
@@ -200,6 +323,71 @@ describe("my-project", () => {
   });
 
 
+  it('receive and call', async () => {
+    const token = tokenMint1.publicKey;
+    const callAuthority = getCallAuthority(user.publicKey, program.programId);
+    const escrow = getAssociatedTokenAddressSync(token, callAuthority, true);
+    const destination = await getOrCreateUserATA(connection, admin, token);
+
+    // call the token program to move received tokens from the escrow to `destination`;
+    // instruction data is `Transfer` (3) followed by the appended amount
+    const callData = Buffer.concat([TOKEN_PROGRAM_ID.toBuffer(), Buffer.from([1, 0, 0, 0]), Buffer.from([3])]);  // borsh `CallData`
+    const receiveAndCall = async (skipCall: boolean) => {
+      const { receiverNonce } = await getStateSnapshot(token, user.publicKey);
+      const value: ReceivePayload = {
+        to: user.publicKey.toBytes(),
+        tokenAddressTo: token.toBytes(),
+        amountTo: 10,
+        chainTo: SOLANA_CHAIN_ID,
+        chainFrom: AMB_CHAIN_ID,
+        eventId: 1,
+        expiresAt: receiveExpiry(),
+        signerEpoch: await getSignerEpoch(),
+        source: noSource(),
+        flags: new Uint8Array(32),
+        flagData: Buffer.concat([numberToUint8Array(receiverNonce, 8), encodeFlagData([[FlagDataTag.CALL, callData]])]),
+      };
+      const receiveInstruction = await bridgeProgram.methods
+        .receiveAndCall(new BN(value.amountTo), new BN(value.eventId), new BN(value.expiresAt), sourceArg(value.source), [...value.flags], Buffer.from(value.flagData), skipCall)
+        .accountsPartial({ receiver: user.publicKey, mint: token, targetProgram: TOKEN_PROGRAM_ID })
+        .remainingAccounts([
+          { pubkey: escrow, isSigner: false, isWritable: true },
+          { pubkey: destination, isSigner: false, isWritable: true },
+          { pubkey: callAuthority, isSigner: false, isWritable: false },
+        ])
+        .instruction();
+      const verifyInstruction = verifySignatureInstruction(signMessage(serializeReceivePayload(value), receiveSigners));
+      const tx = new Transaction().add(verifyInstruction, receiveInstruction);
+      return sendAndConfirmTransaction(connection, tx, [user], { commitment: 'confirmed' });
+    };
+    const balance = async (account: PublicKey) => +(await connection.getTokenAccountBalance(account)).value.amount;
+
+    // not whitelisted - delivered to the receiver
+    let before = await getStateSnapshot(token, user.publicKey);
+    await receiveAndCall(false);
+    expect((await getStateSnapshot(token, user.publicKey)).token.user).to.eq(before.token.user + 10);
+
+    await expect(
+      bridgeProgram.methods.setCallTarget(TOKEN_PROGRAM_ID, true).accountsPartial({ admin: user.publicKey }).signers([user]).rpc()
+    ).to.be.rejectedWith("Not an admin");
+    await bridgeProgram.methods.setCallTarget(TOKEN_PROGRAM_ID, true).accountsPartial({ admin: admin.publicKey }).signers([admin]).rpc();
+
+    const destinationBefore = await balance(destination);
+    await receiveAndCall(false);
+    expect(await balance(destination)).to.eq(destinationBefore + 10);
+    expect(await balance(escrow)).to.eq(0);
+
+    // tokens left in the receiver's escrow block its calls, the receiver can still opt out below
+    await transfer(connection, admin, destination, escrow, admin, 1);
+    await expect(receiveAndCall(false)).to.be.rejectedWith("Call escrow is not empty");
+
+    // receiver can opt out of the call
+    before = await getStateSnapshot(token, user.publicKey);
+    await receiveAndCall(true);
+    expect((await getStateSnapshot(token, user.publicKey)).token.user).to.eq(before.token.user + 10);
+  });
+
+
   it('receive mintable token', async () => {
 
     const tokenTo = tokenMint2.publicKey;
@@ -238,7 +426,7 @@ describe("my-project", () => {
     expect(after.token.user).to.eq(before.token.user - 50);
     expect(before.token.bridge).to.eq(undefined);  // bridge ATA for mintable tokens doesn't exist
     expect(after.token.bridge).to.eq(undefined);
-    expect(after.native.user).to.eq(before.native.user - 20);
+    expect(after.native.user).to.eq(before.native.user - 20 - await getQuoteRent());  // fee + rent for consumed quote
     expect(after.native.bridge).to.eq(before.native.bridge + 20);
 
 
@@ -246,6 +434,501 @@ describe("my-project", () => {
   });
 
 
+  it('send batch', async () => {
+    const tokens = [tokenMint1.publicKey, tokenMint2.publicKey];
+    await commonReceive(user, tokenMint2.publicKey, 40, (await getStateSnapshot(tokenMint2.publicKey, user.publicKey)).receiverNonce, true);
+    const before = await Promise.all(tokens.map((token) => getStateSnapshot(token, user.publicKey)));
+
+    const quoteId = nextQuoteId();
+    const timestamp = Math.floor(Date.now() / 1000);
+    const value: SendBatchPayload = {
+      sender: user.publicKey.toBytes(),
+      recipient: ambUserAddress,
+      legs: [
+        { tokenAddressFrom: tokenMint1.publicKey.toBytes(), tokenAddressTo: ambTokenAddress1, amountToSend: 30, flags: new Uint8Array(32), flagData: new Uint8Array(0) },
+        { tokenAddressFrom: tokenMint2.publicKey.toBytes(), tokenAddressTo: ambTokenAddress2, amountToSend: 40, flags: new Uint8Array(32), flagData: new Uint8Array(0) },
+      ],
+      feeAmount: 20,
+      chainFrom: SOLANA_CHAIN_ID,
+      chainTo: AMB_CHAIN_ID,
+      timestamp,
+      quoteId,
+    };
+    const payload = serializeSendBatchPayload(value);
+    const sendBatchInstruction = await bridgeProgram.methods
+      .sendBatch(payload, new BN(quoteId), new BN(20), new BN(timestamp + 60))
+      .accountsPartial({ sender: user.publicKey })
+      .remainingAccounts(await getSendBatchAccounts(program, user.publicKey, tokens, true))
+      .instruction();
+    const tx = new Transaction().add(verifySignatureInstruction(signMessage(payload, [sendSigner])), sendBatchInstruction);
+    const txSignature = await sendAndConfirmTransaction(connection, tx, [user], { commitment: 'confirmed' });
+
+    const after = await Promise.all(tokens.map((token) => getStateSnapshot(token, user.publicKey)));
+    expect(after[0].token.user).to.eq(before[0].token.user - 30);
+    expect(after[0].token.bridge).to.eq(before[0].token.bridge + 30);
+    expect(after[1].token.user).to.eq(before[1].token.user - 40);
+    expect(after[1].native.bridge).to.eq(before[1].native.bridge + 20);  // fee is paid once
+
+    const events = await getEvents(txSignature);
+    expect(events.map((e) => +e.data.eventId)).to.deep.eq([before[0].sendNonce + 1, before[0].sendNonce + 2]);
+
+    // each leg got its receipt
+    for (const [i, token] of tokens.entries()) {
+      const receipt = await program.account.sendReceipt.fetch(getSendReceiptPda(new BN(before[0].sendNonce + 1 + i), program.programId));
+      expect(receipt.sender.equals(user.publicKey)).to.eq(true);
+      expect(receipt.event.tokenAddressFrom.equals(token)).to.eq(true);
+      expect(+receipt.event.eventId).to.eq(before[0].sendNonce + 1 + i);
+    }
+  });
+
+
+  it('send through cpi', async () => {
+    const token = tokenMint1.publicKey;
+    const before = await getStateSnapshot(token, user.publicKey);
+
+    const quoteId = nextQuoteId();
+    const timestamp = Math.floor(Date.now() / 1000);
+    const value: SendPayload = {
+      sender: user.publicKey.toBytes(),
+      recipient: ambUserAddress,
+      tokenAddressFrom: token.toBytes(),
+      tokenAddressTo: ambTokenAddress1,
+      amountToSend: 25,
+      feeAmount: 20,
+      chainFrom: SOLANA_CHAIN_ID,
+      chainTo: AMB_CHAIN_ID,
+      timestamp,
+      quoteId,
+      flags: new Uint8Array(32),
+      flagData: new Uint8Array(0),
+    };
+    const payload = serializeSendPayload(value);
+    const [bridgeToken, bridgeTokenAccount] = getBridgeTokenAccounts(token, program.programId);
+    const integratorInstruction = await integratorProgram.methods
+      .bridgeTokens(payload, new BN(quoteId), new BN(20), new BN(timestamp + 60))
+      .accountsStrict({
+        state: getBridgeStateAccount(program.programId),
+        sender: user.publicKey,
+        senderTokenAccount: getAssociatedTokenAddressSync(token, user.publicKey),
+        bridgeToken,
+        bridgeTokenAccount,
+        mint: token,
+        consumedQuote: getConsumedQuotePda(user.publicKey, new BN(quoteId), program.programId),
+        sendAccumulator: getSendAccumulatorPda(program.programId),
+        ixSysvar: SYSVAR_INSTRUCTIONS_PUBKEY,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+        bridgeProgram: program.programId,
+      })
+      .instruction();
+
+    // the signature doesn't have to be right before the bridge instruction
+    const tx = new Transaction().add(
+      verifySignatureInstruction(signMessage(payload, [sendSigner])),
+      ComputeBudgetProgram.setComputeUnitLimit({ units: 400_000 }),
+      integratorInstruction,
+    );
+    const txSignature = await sendAndConfirmTransaction(connection, tx, [user], { commitment: 'confirmed' });
+
+    const after = await getStateSnapshot(token, user.publicKey);
+    expect(after.token.user).to.eq(before.token.user - 25);
+    expect(after.token.bridge).to.eq(before.token.bridge + 25);
+    expect(after.sendNonce).to.eq(before.sendNonce + 1);
+
+    const events = await getEvents(txSignature);
+    expect(events.map((e) => e.name)).to.deep.eq(['sendEventV2']);
+  });
+
+
+  it('send delegated', async () => {
+    const token = tokenMint1.publicKey;
+    const delegate = Keypair.generate();
+    await requestSol(delegate, connection, 10 ** 9);
+    const userAta = getAssociatedTokenAddressSync(token, user.publicKey);
+    await approve(connection, user, userAta, delegate.publicKey, user, 15, [], { commitment: 'confirmed' });
+
+    const before = await getStateSnapshot(token, user.publicKey);
+    const sendDelegated = async (amountToSend: number) => {
+      const quoteId = nextQuoteId();
+      const timestamp = Math.floor(Date.now() / 1000);
+      const payload = serializeSendPayload({
+        sender: delegate.publicKey.toBytes(),  // the quote is for the delegate
+        recipient: ambUserAddress,
+        tokenAddressFrom: token.toBytes(),
+        tokenAddressTo: ambTokenAddress1,
+        amountToSend,
+        feeAmount: 20,
+        chainFrom: SOLANA_CHAIN_ID,
+        chainTo: AMB_CHAIN_ID,
+        timestamp,
+        quoteId,
+        flags: new Uint8Array(32),
+        flagData: new Uint8Array(0),
+      });
+      const sendInstruction = await bridgeProgram.methods
+        .sendDelegated(payload, new BN(quoteId), new BN(20), new BN(timestamp + 60))
+        .accountsPartial({ sender: delegate.publicKey, sourceTokenAccount: userAta, mint: token })
+        .instruction();
+      const tx = new Transaction().add(verifySignatureInstruction(signMessage(payload, [sendSigner])), sendInstruction);
+      return await sendAndConfirmTransaction(connection, tx, [delegate], { commitment: 'confirmed' });
+    };
+
+    const txSignature = await sendDelegated(15);
+    const after = await getStateSnapshot(token, user.publicKey);
+    expect(after.token.user).to.eq(before.token.user - 15);
+    expect(after.token.bridge).to.eq(before.token.bridge + 15);
+
+    const [event] = await getEvents(txSignature);
+    expect(event.data.from.toBase58()).to.eq(user.publicKey.toBase58());  // owner, not the delegate
+
+    // the approval is used up
+    await expect(sendDelegated(1)).to.be.rejected;
+  });
+
+
+  it('receive batch', async () => {
+    const tokens = [tokenMint1.publicKey, tokenMint2.publicKey];
+    const amounts = [10, 15];
+    const before = await Promise.all(tokens.map((token) => getStateSnapshot(token, user.publicKey)));
+    const signerEpoch = await getSignerEpoch();
+
+    const values: ReceivePayload[] = tokens.map((token, i) => ({
+      to: user.publicKey.toBytes(),
+      tokenAddressTo: token.toBytes(),
+      amountTo: amounts[i],
+      chainFrom: AMB_CHAIN_ID,
+      chainTo: SOLANA_CHAIN_ID,
+      eventId: 100 + i,
+      expiresAt: receiveExpiry(),
+      signerEpoch,
+      source: noSource(),
+      flags: new Uint8Array(32),
+      flagData: numberToUint8Array(before[0].receiverNonce + i, 8),  // consecutive nonces
+    }));
+    const payload = serializeReceiveBatchPayloads(values);
+    const legs = values.map((value) => ({
+      amountTo: new BN(value.amountTo),
+      eventId: new BN(value.eventId),
+      expiresAt: new BN(value.expiresAt),
+      source: sourceArg(value.source),
+      flags: [...value.flags],
+      flagData: Buffer.from(value.flagData),
+    }));
+    const receiveBatch = async () => {
+      const receiveBatchInstruction = await bridgeProgram.methods
+        .receiveBatch(legs)
+        .accountsPartial({ receiver: user.publicKey })
+        .remainingAccounts(await getReceiveBatchAccounts(program, user.publicKey, tokens))
+        .instruction();
+      const tx = new Transaction().add(verifySignatureInstruction(signMessage(payload, receiveSigners)), receiveBatchInstruction);
+      return await sendAndConfirmTransaction(connection, tx, [user], { commitment: 'confirmed' });
+    };
+    const txSignature = await receiveBatch();
+
+    const after = await Promise.all(tokens.map((token) => getStateSnapshot(token, user.publicKey)));
+    expect(after[0].token.user).to.eq(before[0].token.user + 10);
+    expect(after[0].token.bridge).to.eq(before[0].token.bridge - 10);
+    expect(after[1].token.user).to.eq(before[1].token.user + 15);
+    expect(after[0].receiverNonce).to.eq(before[0].receiverNonce + 2);
+
+    const events = await getEvents(txSignature);
+    expect(events.map((e) => +e.data.eventId)).to.deep.eq([100, 101]);
+
+    // nonces are used up, the same batch can't be claimed twice
+    await expect(receiveBatch()).to.be.rejected;
+  });
+
+
+  it('receive to pda and token account', async () => {
+    const token = tokenMint1.publicKey;
+    const receiveTo = async (to: PublicKey, receiverTokenAccount: PublicKey, flagData: Uint8Array) => {
+      const value: ReceivePayload = {
+        to: to.toBytes(),
+        tokenAddressTo: token.toBytes(),
+        amountTo: 7,
+        chainFrom: AMB_CHAIN_ID,
+        chainTo: SOLANA_CHAIN_ID,
+        eventId: 1,
+        expiresAt: receiveExpiry(),
+        signerEpoch: await getSignerEpoch(),
+        source: noSource(),
+        flags: new Uint8Array(32),
+        flagData,
+      };
+      const receiveInstruction = await bridgeProgram.methods
+        .receiveTo(to, new BN(value.amountTo), new BN(value.eventId), new BN(value.expiresAt), sourceArg(value.source), [...value.flags], Buffer.from(value.flagData))
+        .accountsPartial({ payer: user.publicKey, recipient: to, receiverTokenAccount, mint: token })
+        .instruction();
+      const verifyInstruction = verifySignatureInstruction(signMessage(serializeReceivePayload(value), receiveSigners));
+      const tx = new Transaction().add(verifyInstruction, receiveInstruction);
+      return sendAndConfirmTransaction(connection, tx, [user], { commitment: 'confirmed' });
+    };
+    const balance = async (account: PublicKey) => +(await connection.getTokenAccountBalance(account)).value.amount;
+
+    // off-curve owner, its ATA is created by the bridge; `user` only relays
+    const [pda] = PublicKey.findProgramAddressSync([Buffer.from("vault")], SystemProgram.programId);
+    const pdaAta = getAssociatedTokenAddressSync(token, pda, true);
+    await receiveTo(pda, pdaAta, numberToUint8Array(0, 8));
+    expect(await balance(pdaAta)).to.eq(7);
+
+    // explicit non-associated token account
+    const tokenAccount = await createAccount(connection, admin, token, admin.publicKey, Keypair.generate(), { commitment: 'confirmed' });
+    const toTokenAccount = encodeFlagData([[FlagDataTag.TO_TOKEN_ACCOUNT, new Uint8Array(0)]]);
+    await receiveTo(tokenAccount, tokenAccount, Buffer.concat([numberToUint8Array(0, 8), toTokenAccount]));
+    expect(await balance(tokenAccount)).to.eq(7);
+
+    // the payload commits to the form: without the tag `to` is an owner, so its ATA is expected
+    await expect(receiveTo(tokenAccount, tokenAccount, numberToUint8Array(1, 8))).to.be.rejectedWith("Invalid input arguments");
+  });
+
+
+  it('receive with native drop-off', async () => {
+    const setNativeSendAmount = (amount: number) =>
+      bridgeProgram.methods.setNativeSendAmount(new BN(amount)).accountsPartial({ authority: admin.publicKey, authorityRoles: null }).signers([admin]).rpc();
+    await setNativeSendAmount(15);
+
+    const sendNativeFlags = makeFlags(Flags.SEND_NATIVE_TO_RECEIVER);
+
+    const before = await getStateSnapshot(tokenMint1.publicKey, user.publicKey);
+    await commonReceive(user, tokenMint1.publicKey, 5, before.receiverNonce, false, false, sendNativeFlags);
+    const afterFlagged = await getStateSnapshot(tokenMint1.publicKey, user.publicKey);
+    expect(afterFlagged.native.bridge).to.eq(before.native.bridge - 15);
+    expect(afterFlagged.token.user).to.eq(before.token.user + 5);
+
+    // no flag, no drop-off
+    await commonReceive(user, tokenMint1.publicKey, 5, afterFlagged.receiverNonce);
+    const after = await getStateSnapshot(tokenMint1.publicKey, user.publicKey);
+    expect(after.native.bridge).to.eq(afterFlagged.native.bridge);
+
+    // non-admin can't change the amount
+    await expect(
+      bridgeProgram.methods.setNativeSendAmount(new BN(1)).accountsPartial({ authority: user.publicKey, authorityRoles: null }).signers([user]).rpc()
+    ).to.be.rejected;
+    await setNativeSendAmount(0);
+  });
+
+
+  it('receive expired payload', async () => {
+    const { receiverNonce } = await getStateSnapshot(tokenMint1.publicKey, user.publicKey);
+    const expiredAt = Math.floor(Date.now() / 1000) - 60;
+    await expect(
+      commonReceive(user, tokenMint1.publicKey, 5, receiverNonce, false, false, new Uint8Array(32), expiredAt)
+    ).to.be.rejectedWith("Receive payload expired");
+  });
+
+
+  it('versioned events', async () => {
+    const token = tokenMint1.publicKey;
+
+    const timestamp = Math.floor(Date.now() / 1000) - 5;
+    const sendTx = await commonSend(user, token, ambUserAddress, ambTokenAddress1, 12, false, [], 30, nextQuoteId(), timestamp);
+    const [sendEvent] = await getEvents(sendTx.transaction.signatures[0]);
+    expect(sendEvent.name).to.eq('sendEventV2');
+    expect(sendEvent.data.schemaVersion).to.eq(2);
+    expect(+sendEvent.data.feeAmount).to.eq(30);
+    expect(sendEvent.data.feeToken.equals(NATIVE_MINT)).to.eq(true);
+    expect(+sendEvent.data.quoteTimestamp).to.eq(timestamp);
+    expect(+sendEvent.data.slot).to.eq(sendTx.slot);
+
+    const { receiverNonce } = await getStateSnapshot(token, user.publicKey);
+    const source = { receiptHash: new Uint8Array(32).fill(3), txHash: new Uint8Array(32).fill(7) };
+    const value: ReceivePayload = {
+      to: user.publicKey.toBytes(),
+      tokenAddressTo: token.toBytes(),
+      amountTo: 5,
+      chainFrom: AMB_CHAIN_ID,
+      chainTo: SOLANA_CHAIN_ID,
+      eventId: 1,
+      expiresAt: receiveExpiry(),
+      signerEpoch: await getSignerEpoch(),
+      source,
+      flags: new Uint8Array(32),
+      flagData: numberToUint8Array(receiverNonce, 8),
+    };
+    const receiveInstruction = await bridgeProgram.methods
+      .receive(new BN(value.amountTo), new BN(value.eventId), new BN(value.expiresAt), sourceArg(value.source), [...value.flags], Buffer.from(value.flagData))
+      .accountsPartial({ receiver: user.publicKey, mint: token })
+      .instruction();
+    const verifyInstruction = verifySignatureInstruction(signMessage(serializeReceivePayload(value), receiveSigners));
+    const tx = new Transaction().add(verifyInstruction, receiveInstruction);
+    const txSignature = await sendAndConfirmTransaction(connection, tx, [user], { commitment: 'confirmed' });
+
+    const [receiveEvent] = await getEvents(txSignature);
+    expect(receiveEvent.name).to.eq('receiveEventV2');
+    expect(receiveEvent.data.schemaVersion).to.eq(2);
+    expect(receiveEvent.data.receiptHash).to.deep.eq([...source.receiptHash]);
+    expect(receiveEvent.data.sourceTxHash).to.deep.eq([...source.txHash]);
+
+    // the source is signed
+    const forged = { ...value, source: { ...source, txHash: new Uint8Array(32).fill(8) } };
+    const forgedInstruction = await bridgeProgram.methods
+      .receive(new BN(value.amountTo), new BN(value.eventId), new BN(value.expiresAt), sourceArg(forged.source), [...value.flags], Buffer.from(value.flagData))
+      .accountsPartial({ receiver: user.publicKey, mint: token })
+      .instruction();
+    await expect(sendAndConfirmTransaction(connection, new Transaction().add(verifyInstruction, forgedInstruction), [user], { commitment: 'confirmed' }))
+      .to.be.rejectedWith("Signature invalid");
+  });
+
+
+  it('refund', async () => {
+    const token = tokenMint1.publicKey;
+    await commonSend(user, token, ambUserAddress, ambTokenAddress1, 12);
+    const before = await getStateSnapshot(token, user.publicKey);
+    const eventId = before.sendNonce;  // the send above
+
+    const value: RefundPayload = {
+      eventId,
+      sender: user.publicKey.toBytes(),
+      tokenAddress: token.toBytes(),
+      amount: 12,
+      chainFrom: SOLANA_CHAIN_ID,
+      chainTo: AMB_CHAIN_ID,
+    };
+    const refund = async () => {
+      const refundInstruction = await bridgeProgram.methods
+        .refund(new BN(eventId), new BN(value.amount))
+        .accountsPartial({ payer: admin.publicKey, sender: user.publicKey, mint: token })
+        .instruction();
+      const verifyInstruction = verifySignatureInstruction(signMessage(serializeRefundPayload(value), receiveSigners));
+      const tx = new Transaction().add(verifyInstruction, refundInstruction);
+      return sendAndConfirmTransaction(connection, tx, [admin], { commitment: 'confirmed' });
+    };
+    // a paused token still pays out refunds
+    const [bridgeToken] = getBridgeTokenAccounts(token, program.programId);
+    const setTokenPause = (pause: boolean) =>
+      bridgeProgram.methods.setTokenPause(pause).accountsPartial({ authority: admin.publicKey, authorityRoles: null, bridgeToken }).signers([admin]).rpc();
+    await setTokenPause(true);
+    const txSignature = await refund();
+    await setTokenPause(false);
+
+    const after = await getStateSnapshot(token, user.publicKey);
+    expect(after.token.user).to.eq(before.token.user + 12);
+    expect(after.token.bridge).to.eq(before.token.bridge - 12);
+
+    const refunded = await program.account.refundedSend.fetch(getRefundedSendPda(new BN(eventId), program.programId));
+    expect(+refunded.amount).to.eq(12);
+    const [event] = await getEvents(txSignature);
+    expect(event.name).to.eq('sendRefunded');
+
+    // a send is refunded once
+    await expect(refund()).to.be.rejected;
+  });
+
+
+  it('send receipt', async () => {
+    const token = tokenMint1.publicKey;
+    const { sendNonce } = await getStateSnapshot(token, user.publicKey);
+    const eventId = sendNonce + 1;  // the send below
+    const receiptPda = getSendReceiptPda(new BN(eventId), program.programId);
+
+    await commonSend(user, token, ambUserAddress, ambTokenAddress1, 12, false, [], 20, nextQuoteId(), undefined, undefined, undefined, receiptPda);
+    const receipt = await program.account.sendReceipt.fetch(receiptPda);
+    expect(receipt.sender.equals(user.publicKey)).to.eq(true);
+    expect(+receipt.event.eventId).to.eq(eventId);
+    expect(+receipt.event.amountFrom).to.eq(12);
+
+    // same hash as `ReceiptUtils.toHash` on AMB
+    const packed = Buffer.concat([
+      new Uint8Array(12), ambUserAddress,
+      new Uint8Array(12), ambTokenAddress1,
+      Buffer.from(receipt.event.amountTo),
+      numberToUint8Array(SOLANA_CHAIN_ID, 32),
+      numberToUint8Array(AMB_CHAIN_ID, 32),
+      numberToUint8Array(eventId, 32),
+      new Uint8Array(32),  // flags >> 65
+    ]);
+    const ethSigned = keccak_256(Buffer.concat([Buffer.from("\x19Ethereum Signed Message:\n32"), keccak_256(packed)]));
+    expect(receipt.hash).to.deep.eq([...ethSigned]);
+
+    const value: ReceiptClaimedPayload = {
+      eventId,
+      receiptHash: Uint8Array.from(receipt.hash),
+      chainFrom: SOLANA_CHAIN_ID,
+      chainTo: AMB_CHAIN_ID,
+    };
+    const closeReceipt = async (payload: ReceiptClaimedPayload) => {
+      const closeInstruction = await bridgeProgram.methods
+        .closeSendReceipt(new BN(eventId))
+        .accountsPartial({ receipt: receiptPda, sender: user.publicKey })
+        .instruction();
+      const verifyInstruction = verifySignatureInstruction(signMessage(serializeReceiptClaimedPayload(payload), receiveSigners));
+      const tx = new Transaction().add(verifyInstruction, closeInstruction);
+      return sendAndConfirmTransaction(connection, tx, [admin], { commitment: 'confirmed' });
+    };
+
+    // only a confirmation of this exact receipt closes it
+    await expect(closeReceipt({ ...value, receiptHash: new Uint8Array(32) })).to.be.rejected;
+
+    const rent = await connection.getBalance(receiptPda);
+    const before = await getStateSnapshot(token, user.publicKey);
+    await closeReceipt(value);
+    const after = await getStateSnapshot(token, user.publicKey);
+    expect(after.native.user).to.eq(before.native.user + rent);
+    expect(await connection.getAccountInfo(receiptPda)).to.eq(null);
+  });
+
+
+  it('send accumulator', async () => {
+    const token = tokenMint1.publicKey;
+    const accumulatorPda = getSendAccumulatorPda(program.programId);
+    const stateBefore = await program.account.globalState.fetch(getBridgeStateAccount(program.programId));
+    const before = await program.account.sendAccumulator.fetch(accumulatorPda);
+
+    await commonSend(user, token, ambUserAddress, ambTokenAddress1, 12);
+
+    const stateAfter = await program.account.globalState.fetch(getBridgeStateAccount(program.programId));
+    const after = await program.account.sendAccumulator.fetch(accumulatorPda);
+    // every send is a leaf, in event id order
+    expect(+after.count).to.eq(+before.count + 1);
+    expect(+after.firstEventId + +after.count - 1).to.eq(+stateAfter.nonce);
+    expect(stateAfter.sendRoot).to.not.deep.eq(stateBefore.sendRoot);
+
+    // sends go without the accumulator only until it's created
+    const now = Math.floor(Date.now() / 1000);
+    await expect(
+      commonSend(user, token, ambUserAddress, ambTokenAddress1, 12, false, [], 20, nextQuoteId(), now, 20, now + 60, null, null)
+    ).to.be.rejectedWith("Send accumulator is required");
+  });
+
+
+  it('skip nonce', async () => {
+    const token = tokenMint1.publicKey;
+    const before = await getStateSnapshot(token, user.publicKey);
+
+    const value: SkipNoncePayload = {
+      receiver: user.publicKey.toBytes(),
+      currentNonce: before.receiverNonce,
+      newNonce: before.receiverNonce + 2,
+      voidedEventIds: [200, 201],
+      chainFrom: AMB_CHAIN_ID,
+      chainTo: SOLANA_CHAIN_ID,
+    };
+    const skipNonce = async () => {
+      const skipNonceInstruction = await bridgeProgram.methods
+        .skipNonce(user.publicKey, new BN(value.newNonce), value.voidedEventIds.map((id) => new BN(id)))
+        .accountsPartial({ payer: admin.publicKey })
+        .instruction();
+      const verifyInstruction = verifySignatureInstruction(signMessage(serializeSkipNoncePayload(value), receiveSigners));
+      const tx = new Transaction().add(verifyInstruction, skipNonceInstruction);
+      return sendAndConfirmTransaction(connection, tx, [admin], { commitment: 'confirmed' });
+    };
+    const txSignature = await skipNonce();
+
+    const after = await getStateSnapshot(token, user.publicKey);
+    expect(after.receiverNonce).to.eq(before.receiverNonce + 2);
+    const [event] = await getEvents(txSignature);
+    expect(event.name).to.eq('nonceSkipped');
+    expect(event.data.voidedEventIds.map((id) => +id)).to.deep.eq([200, 201]);
+
+    // the skipped nonces can't be received anymore, the next one can
+    await expect(commonReceive(user, token, 5, before.receiverNonce)).to.be.rejected;
+    await commonReceive(user, token, 5, after.receiverNonce);
+
+    // the signature commits to the current nonce, so it's usable once
+    await expect(skipNonce()).to.be.rejected;
+  });
+
+
   it('send native', async () => {
     const userFrom = user;
     const tokenFrom = NATIVE_MINT;
@@ -328,13 +1011,93 @@ describe("my-project", () => {
     expect(after.native.user).to.be.greaterThanOrEqual(500 * 10 ** 9);
   });
 
+  it("send message", async () => {
+    const quoteId = nextQuoteId();
+    const timestamp = Math.floor(Date.now() / 1000);
+    const value: SendMessagePayload = {
+      sender: user.publicKey.toBytes(),
+      target: ambUserAddress,
+      payload: Buffer.from("hello amb"),
+      feeAmount: 20,
+      chainFrom: SOLANA_CHAIN_ID,
+      chainTo: AMB_CHAIN_ID,
+      timestamp,
+      quoteId,
+    };
+    const payload = serializeSendMessagePayload(value);
+    const before = await getStateSnapshot(tokenMint1.publicKey, user.publicKey);
+
+    const sendMessageInstruction = await bridgeProgram.methods
+      .sendMessage(payload, new BN(quoteId), new BN(20), new BN(timestamp + 60))
+      .accountsPartial({ sender: user.publicKey })
+      .instruction();
+    const tx = new Transaction().add(verifySignatureInstruction(signMessage(payload, [sendSigner])), sendMessageInstruction);
+    const txSignature = await sendAndConfirmTransaction(connection, tx, [user], { commitment: 'confirmed' });
+
+    const [event] = await getEvents(txSignature);
+    expect(event.name).to.eq("messageSent");
+    expect(+event.data.eventId).to.eq(before.sendNonce + 1);
+    expect(Buffer.from(event.data.payload).toString()).to.eq("hello amb");
+    // messages get no receipt, AMB has no receipt hash for them
+    expect(await connection.getAccountInfo(getSendReceiptPda(new BN(before.sendNonce + 1), program.programId))).to.eq(null);
+
+    // the message is a leaf of the accumulator too, so a transfer after it still gets the next event id
+    const accumulator = await program.account.sendAccumulator.fetch(getSendAccumulatorPda(program.programId));
+    expect(+accumulator.firstEventId + +accumulator.count - 1).to.eq(before.sendNonce + 1);
+    const sendTx = await commonSend(user, tokenMint1.publicKey, ambUserAddress, ambTokenAddress1, 12);
+    const [sendEvent] = await getEvents(sendTx.transaction.signatures[0]);
+    expect(+sendEvent.data.eventId).to.eq(before.sendNonce + 2);
+  });
+
+
+  it("receive message", async () => {
+    const value: ReceiveMessagePayload = {
+      sender: ambUserAddress,
+      target: user.publicKey.toBytes(),
+      chainFrom: AMB_CHAIN_ID,
+      chainTo: SOLANA_CHAIN_ID,
+      eventId: 7,
+      expiresAt: receiveExpiry(),
+      signerEpoch: await getSignerEpoch(),
+      payload: Buffer.from("hello solana"),
+    };
+
+    const receiveMessage = async (value: ReceiveMessagePayload) => {
+      const signature = signMessage(serializeReceiveMessagePayload(value), receiveSigners);
+      const receiveMessageInstruction = await bridgeProgram.methods
+        .receiveMessage([...value.sender], user.publicKey, new BN(value.eventId), new BN(value.expiresAt), Buffer.from(value.payload))
+        .accountsPartial({ payer: user.publicKey })
+        .instruction();
+      const tx = new Transaction().add(verifySignatureInstruction(signature), receiveMessageInstruction);
+      return sendAndConfirmTransaction(connection, tx, [user], { commitment: 'confirmed' });
+    };
+
+    // same rules as receives: not after the expiry, not under another signer epoch, bounded payload
+    await expect(receiveMessage({ ...value, expiresAt: Math.floor(Date.now() / 1000) - 60 })).to.be.rejectedWith("Receive payload expired");
+    await expect(receiveMessage({ ...value, signerEpoch: value.signerEpoch as number + 1 })).to.be.rejectedWith("Signature invalid");
+    await expect(receiveMessage({ ...value, payload: Buffer.alloc(513) })).to.be.rejected;
+
+    await receiveMessage(value);
+    const receivedMessage = await program.account.receivedMessage.fetch(getReceivedMessagePda(new BN(value.eventId), program.programId));
+    expect(receivedMessage.target.equals(user.publicKey)).to.eq(true);
+    expect(receivedMessage.sender).to.deep.eq([...ambUserAddress]);
+
+    // each message can be received once
+    await expect(receiveMessage(value)).to.be.rejectedWith("already in use");
+  });
+
+
   it("pause", async () => {
+    const setPause = (flags: PauseFlags, pause: boolean, reason = 0) =>
+      bridgeProgram.methods.setPause(flags, pause, reason).accountsPartial({ authority: admin.publicKey, authorityRoles: null }).signers([admin]).rpc();
+
     let state = await program.account.globalState.fetch(getBridgeStateAccount(program.programId));
-    expect(state.pause).to.eq(false);
+    expect(state.pauseFlags).to.eq(0);
 
-    await bridgeProgram.methods.setPause(true).accountsPartial({ admin: admin.publicKey, }).signers([admin]).rpc();
+    await setPause(PauseFlags.ALL, true, 1);
     state = await program.account.globalState.fetch(getBridgeStateAccount(program.programId));
-    expect(state.pause).to.eq(true);
+    expect(state.pauseFlags).to.eq(PauseFlags.ALL);
+    expect(state.pauseReason).to.eq(1);
 
     // dunno why, error message not parsed well, so "6005" instead of "Bridge is paused"
 
@@ -346,16 +1109,70 @@ describe("my-project", () => {
       commonReceive(user, tokenMint1.publicKey, 50, 0)
     ).to.be.rejectedWith("6005");
 
-    await bridgeProgram.methods.setPause(false).accountsPartial({ admin: admin.publicKey, }).signers([admin]).rpc()
+    await expect(
+      initializeToken(program, admin, tokenMint1.publicKey, ambTokenAddress1_, 18, false)
+    ).to.be.rejectedWith("Bridge is paused");
+
+    // unpause only receive
+    await setPause(PauseFlags.RECEIVE, false, 2);
+    state = await program.account.globalState.fetch(getBridgeStateAccount(program.programId));
+    expect(state.pauseFlags).to.eq(PauseFlags.ALL & ~PauseFlags.RECEIVE);
+    expect(state.pauseReason).to.eq(2);
+
+    await expect(
+      commonSend(user, tokenMint1.publicKey, ambUserAddress, ambTokenAddress1, 50)
+    ).to.be.rejectedWith("6005");
+    const before = await getStateSnapshot(tokenMint2.publicKey, user.publicKey);
+    await commonReceive(user, tokenMint2.publicKey, 10, before.receiverNonce, true);
+
+    await setPause(PauseFlags.ALL, false);
     state = await program.account.globalState.fetch(getBridgeStateAccount(program.programId));
-    expect(state.pause).to.eq(false);
+    expect(state.pauseFlags).to.eq(0);
+
+  });
+
+
+  it("roles", async () => {
+    const pauser = Keypair.generate();
+    await requestSol(pauser, connection, 10 ** 9);
+    const pauserRoles = getRolesPda(pauser.publicKey, program.programId);
+    const setPause = (pause: boolean) => bridgeProgram.methods.setPause(PauseFlags.ALL, pause, 0)
+      .accountsPartial({ authority: pauser.publicKey, authorityRoles: pauserRoles }).signers([pauser]).rpc();
+
+    await bridgeProgram.methods.grantRole(pauser.publicKey, { pauser: {} }).accountsPartial({ admin: admin.publicKey, }).signers([admin]).rpc();
+    const roles = await program.account.roleAccount.fetch(pauserRoles);
+    expect(roles.member.equals(pauser.publicKey));
+    expect(roles.roles).to.eq(1);
+
+    await setPause(true);
+    expect((await program.account.globalState.fetch(getBridgeStateAccount(program.programId))).pauseFlags).to.eq(PauseFlags.ALL);
+    // only admin can unpause
+    await expect(setPause(false)).to.be.rejectedWith("Not an admin");
+    await bridgeProgram.methods.setPause(PauseFlags.ALL, false, 0).accountsPartial({ authority: admin.publicKey, authorityRoles: null }).signers([admin]).rpc();
 
+    // pauser can't withdraw fees
+    await expect(
+      queueOperation(program, pauser, { withdrawFees: { receiver: pauser.publicKey, amount: new BN(50) } }, pauserRoles)
+    ).to.be.rejectedWith("Missing required role");
+
+    const revokeRole = (member: PublicKey) => bridgeProgram.methods.revokeRole(member, { pauser: {} })
+      .accountsPartial({ admin: admin.publicKey, }).signers([admin]).rpc();
+    await revokeRole(pauser.publicKey);
+    expect((await program.account.roleAccount.fetch(pauserRoles)).roles).to.eq(0);
+    await expect(setPause(true)).to.be.rejectedWith("Missing required role");
+
+    // revoking a role that isn't held fails, and doesn't create a role account
+    await expect(revokeRole(pauser.publicKey)).to.be.rejectedWith("Missing required role");
+    const stranger = Keypair.generate().publicKey;
+    await expect(revokeRole(stranger)).to.be.rejected;
+    expect(await connection.getAccountInfo(getRolesPda(stranger, program.programId))).to.eq(null);
   });
 
 
   it("withdraw fees", async () => {
     const before = await getStateSnapshot(tokenMint1.publicKey, admin.publicKey);
-    await program.methods.withdrawFees(new BN(50)).accounts({ admin: admin.publicKey, }).signers([admin]).rpc();
+    const operation = await queueOperation(program, admin, { withdrawFees: { receiver: admin.publicKey, amount: new BN(50) } });
+    await executeOperation(program, operation);
     const after = await getStateSnapshot(tokenMint1.publicKey, admin.publicKey);
     expect(after.native.bridge).to.eq(before.native.bridge - 50);
     expect(after.native.user).to.eq(before.native.user + 50);
@@ -373,23 +1190,54 @@ describe("my-project", () => {
       ).to.be.rejectedWith("Invalid nonce");
     });
 
+    it('migrate already migrated accounts', async () => {
+      const [bridgeToken] = getBridgeTokenAccounts(tokenMint1.publicKey, program.programId);
+      await expect(
+        bridgeProgram.methods.migrateState(new BN(0)).accountsPartial({ upgradeAuthority: admin.publicKey }).signers([admin]).rpc()
+      ).to.be.rejectedWith("Account is already migrated");
+      await expect(
+        bridgeProgram.methods.migrateTokenConfig().accountsPartial({ account: bridgeToken, payer: user.publicKey }).signers([user]).rpc()
+      ).to.be.rejectedWith("Account is already migrated");
+      await expect(
+        bridgeProgram.methods.migrateNonce().accountsPartial({ account: getUserNoncePda(user.publicKey, program.programId), payer: user.publicKey }).signers([user]).rpc()
+      ).to.be.rejectedWith("Account is already migrated");
+    });
+
     describe('call admin methods with non-admin account', () => {
 
       it("initialize token", async () => {
         await expect(
           initializeToken(program, user, tokenMint1.publicKey, ambTokenAddress1_, 18, true)
-        ).to.be.rejectedWith("Not an admin");
+        ).to.be.rejectedWith("Missing required role");
       });
 
       it("pause", async () => {
         await expect(
-          bridgeProgram.methods.setPause(true).accountsPartial({ admin: user.publicKey, }).signers([user]).rpc()
-        ).to.be.rejectedWith("Not an admin");
+          bridgeProgram.methods.setPause(PauseFlags.ALL, true, 0).accountsPartial({ authority: user.publicKey, authorityRoles: null }).signers([user]).rpc()
+        ).to.be.rejectedWith("Missing required role");
       });
 
       it("withdraw fees", async () => {
         await expect(
-          bridgeProgram.methods.withdrawFees(new BN(50)).accountsPartial({ admin: user.publicKey, }).signers([user]).rpc()
+          queueOperation(program, user, { withdrawFees: { receiver: user.publicKey, amount: new BN(50) } })
+        ).to.be.rejectedWith("Missing required role");
+      });
+
+      it("migrate token", async () => {
+        await expect(
+          queueOperation(program, user, { migrateToken: { mint: tokenMint2.publicKey, successorProgram: user.publicKey } })
+        ).to.be.rejectedWith("Missing required role");
+      });
+
+      it("set guardians", async () => {
+        await expect(
+          bridgeProgram.methods.setGuardians([user.publicKey]).accountsPartial({ authority: user.publicKey, authorityRoles: null }).signers([user]).rpc()
+        ).to.be.rejectedWith("Not an admin");
+      });
+
+      it("grant role", async () => {
+        await expect(
+          bridgeProgram.methods.grantRole(user.publicKey, { pauser: {} }).accountsPartial({ admin: user.publicKey, }).signers([user]).rpc()
         ).to.be.rejectedWith("Not an admin");
       });
 
@@ -488,20 +1336,23 @@ describe("my-project", () => {
         const amountToSend = 50;
 
         const value: SendPayload = {
+          sender: userFrom.publicKey.toBytes(),
+          recipient: userTo,
           tokenAddressFrom: tokenFrom.toBytes(),
           tokenAddressTo: tokenTo,
           amountToSend,
           feeAmount: 20,
           chainFrom: SOLANA_CHAIN_ID,
           chainTo: AMB_CHAIN_ID,
-          timestamp: Date.now(),
+          timestamp: Math.floor(Date.now() / 1000),
+          quoteId: nextQuoteId(),
           flags: new Uint8Array(32),
           flagData: new Uint8Array(0),
         };
 
         const payload = serializeSendPayload(value);
 
-        const sendInstruction = await bridgeProgram.methods.send(payload, [...userTo]).accountsPartial({
+        const sendInstruction = await bridgeProgram.methods.send(payload, new BN(value.quoteId), new BN(20), new BN(Math.floor(Date.now() / 1000) + 60)).accountsPartial({
           sender: userFrom.publicKey,
           mint: tokenFrom,
         }).signers([userFrom]).instruction();
@@ -513,7 +1364,7 @@ describe("my-project", () => {
             tx.feePayer = userFrom.publicKey;
             await sendAndConfirmTransaction(connection, tx, [userFrom], { commitment: 'confirmed' });
           })()
-        ).to.be.rejectedWith("The arguments provided to a program instruction were invalid");
+        ).to.be.rejectedWith("Signature invalid");
 
         // send with wrong instruction
         await expect(
@@ -540,7 +1391,7 @@ describe("my-project", () => {
           (async () => {
             const signature = signMessage(serializeSendPayload(value), [sendSigner]);
             const verifyInstruction = verifySignatureInstruction(signature);
-            const sendInstruction = await bridgeProgram.methods.send(payload, [...userTo]).accountsPartial({
+            const sendInstruction = await bridgeProgram.methods.send(payload, new BN(value.quoteId), new BN(20), new BN(Math.floor(Date.now() / 1000) + 60)).accountsPartial({
               sender: userFrom.publicKey,
               mint: NATIVE_MINT,
             }).signers([userFrom]).instruction();
@@ -568,6 +1419,9 @@ describe("my-project", () => {
         chainTo: SOLANA_CHAIN_ID,
         chainFrom: AMB_CHAIN_ID,
         eventId: 1,
+        expiresAt: receiveExpiry(),
+        signerEpoch: await getSignerEpoch(),
+        source: noSource(),
         flags: new Uint8Array(32),
         flagData: numberToUint8Array(receiveNonce, 8)
       };
@@ -575,6 +1429,8 @@ describe("my-project", () => {
       const receiveInstruction = await bridgeProgram.methods.receive(
         new BN(value.amountTo),
         new BN(value.eventId),
+        new BN(value.expiresAt),
+        sourceArg(value.source),
         [...value.flags],
         Buffer.from(value.flagData)
       ).accountsPartial({
@@ -590,7 +1446,7 @@ describe("my-project", () => {
           tx.feePayer = userTo.publicKey;
           await sendAndConfirmTransaction(connection, tx, [userTo], { commitment: 'confirmed' }); // wait for transaction to be confirmed
         })()
-      ).to.be.rejectedWith("The arguments provided to a program instruction were invalid");
+      ).to.be.rejectedWith("Signature invalid");
 
       // send with wrong instruction
       await expect(
@@ -612,6 +1468,17 @@ describe("my-project", () => {
         })()
       ).to.be.rejectedWith("Signature invalid");
 
+      // send with a signature over the payload without its type tag
+      await expect(
+        (async () => {
+          const signature = signMessage(serializeReceivePayload(value).subarray(8), receiveSigners);
+          const verifyInstruction = verifySignatureInstruction(signature);
+          const tx = new Transaction().add(verifyInstruction, receiveInstruction);
+          tx.feePayer = userTo.publicKey;
+          await sendAndConfirmTransaction(connection, tx, [userTo], { commitment: 'confirmed' }); // wait for transaction to be confirmed
+        })()
+      ).to.be.rejectedWith("Signature invalid");
+
 
       // send with wrong mint account
       await expect(
@@ -621,6 +1488,8 @@ describe("my-project", () => {
           const receiveInstruction = await bridgeProgram.methods.receive(
             new BN(value.amountTo),
             new BN(value.eventId),
+            new BN(value.expiresAt),
+            sourceArg(value.source),
             [...value.flags],
             Buffer.from(value.flagData)
           ).accountsPartial({
@@ -637,23 +1506,195 @@ describe("my-project", () => {
   });
 
 
+  describe("guardian", () => {
+    const guardian = Keypair.generate();
+
+    const emergencyPause = (signer: Keypair, bridgeToken: PublicKey | null, reason: number) =>
+      bridgeProgram.methods.emergencyPause(reason).accountsPartial({ guardian: signer.publicKey, bridgeToken }).signers([signer]).rpc({ commitment: "confirmed" });
+
+    const setTokenPause = (bridgeToken: PublicKey, pause: boolean) =>
+      bridgeProgram.methods.setTokenPause(pause).accountsPartial({ authority: admin.publicKey, authorityRoles: null, bridgeToken }).signers([admin]).rpc();
+
+    before(async () => {
+      await bridgeProgram.methods.setGuardians([guardian.publicKey]).accountsPartial({ authority: admin.publicKey, authorityRoles: null }).signers([admin]).rpc();
+    });
+
+    it("pause globally", async () => {
+      await expect(emergencyPause(user, null, 1)).to.be.rejectedWith("Not a guardian");
+
+      const txSignature = await emergencyPause(guardian, null, 42);
+      const state = await program.account.globalState.fetch(getBridgeStateAccount(program.programId));
+      expect(state.pauseFlags).to.eq(PauseFlags.ALL);
+      expect(state.pauseReason).to.eq(42);
+
+      const [event] = await getEvents(txSignature);
+      expect(event.data.guardian.equals(guardian.publicKey));
+      expect(event.data.token).to.eq(null);
+      expect(event.data.reason).to.eq(42);
+
+      await bridgeProgram.methods.setPause(PauseFlags.ALL, false, 0).accountsPartial({ authority: admin.publicKey, authorityRoles: null }).signers([admin]).rpc();
+    });
+
+    it("pause token", async () => {
+      const [bridgeToken] = getBridgeTokenAccounts(tokenMint1.publicKey, program.programId);
+      await emergencyPause(guardian, bridgeToken, 7);
+      expect((await program.account.tokenConfig.fetch(bridgeToken)).paused).to.eq(true);
+
+      await expect(
+        commonSend(user, tokenMint1.publicKey, ambUserAddress, ambTokenAddress1, 50)
+      ).to.be.rejectedWith("Token is paused");
+
+      // guardian can't unpause
+      await expect(
+        bridgeProgram.methods.setTokenPause(false).accountsPartial({ authority: guardian.publicKey, authorityRoles: null, bridgeToken }).signers([guardian]).rpc()
+      ).to.be.rejectedWith("Missing required role");
+
+      await setTokenPause(bridgeToken, false);
+      expect((await program.account.tokenConfig.fetch(bridgeToken)).paused).to.eq(false);
+    });
+
+    it("admin events", async () => {
+      let txSignature = await bridgeProgram.methods.setGuardians([guardian.publicKey])
+        .accountsPartial({ authority: admin.publicKey, authorityRoles: null }).signers([admin]).rpc({ commitment: "confirmed" });
+      let [event] = await getEvents(txSignature);
+      expect(event.name).to.eq('guardiansChanged');
+      expect(event.data.sender.equals(admin.publicKey)).to.eq(true);
+      expect(event.data.guardians.map((key: PublicKey) => key.toBase58())).to.deep.eq([guardian.publicKey.toBase58()]);
+
+      const [bridgeToken] = getBridgeTokenAccounts(tokenMint1.publicKey, program.programId);
+      for (const pause of [true, false]) {
+        txSignature = await bridgeProgram.methods.setTokenPause(pause)
+          .accountsPartial({ authority: admin.publicKey, authorityRoles: null, bridgeToken }).signers([admin]).rpc({ commitment: "confirmed" });
+        [event] = await getEvents(txSignature);
+        expect(event.name).to.eq('tokenPauseChanged');
+        expect(event.data.token.equals(tokenMint1.publicKey)).to.eq(true);
+        expect(event.data.paused).to.eq(pause);
+      }
+
+      txSignature = await bridgeProgram.methods.setCallTarget(TOKEN_PROGRAM_ID, true)
+        .accountsPartial({ admin: admin.publicKey }).signers([admin]).rpc({ commitment: "confirmed" });
+      [event] = await getEvents(txSignature);
+      expect(event.name).to.eq('callTargetChanged');
+      expect(event.data.programId.equals(TOKEN_PROGRAM_ID)).to.eq(true);
+      expect(event.data.allowed).to.eq(true);
+
+      const mint = Keypair.generate();
+      await createMint(connection, admin, admin.publicKey, null, 6, mint);
+      txSignature = await initializeToken(program, admin, mint.publicKey, ambTokenAddress1_, 18, false);
+      [event] = await getEvents(txSignature);
+      expect(event.name).to.eq('tokenInitialized');
+      expect(event.data.token.equals(mint.publicKey)).to.eq(true);
+      expect(event.data.ambToken).to.deep.eq([...hexToUint8Array(ambTokenAddress1_)]);
+      expect(event.data.ambDecimals).to.eq(18);
+      expect(event.data.isMintable).to.eq(false);
+    });
+  });
+
+
+  describe("timelock", () => {
+    const guardian = Keypair.generate();
+
+    it("execute operation", async () => {
+      const signerEpoch = await getSignerEpoch();
+      const operation = await queueOperation(program, admin, { setSigners: { sendSigner: sendSigner.publicKey, receiveSigner } });
+      const queued = await program.account.queuedOperation.fetch(operation);
+      expect(queued.proposer.equals(admin.publicKey));
+      expect(+queued.eta).to.be.lessThanOrEqual(Date.now() / 1000);
+
+      await executeOperation(program, operation);
+      expect(await connection.getAccountInfo(operation)).to.eq(null);  // closed, rent returned to proposer
+      // same receive signers, pending receive payloads stay valid
+      expect(await getSignerEpoch()).to.eq(signerEpoch);
+
+      // rotate the receive signers away and back
+      const otherReceiveSigner = Keypair.generate().publicKey;
+      await executeOperation(program, await queueOperation(program, admin, { setSigners: { sendSigner: sendSigner.publicKey, receiveSigner: otherReceiveSigner } }));
+      await executeOperation(program, await queueOperation(program, admin, { setSigners: { sendSigner: sendSigner.publicKey, receiveSigner } }));
+      expect(await getSignerEpoch()).to.eq(signerEpoch + 2);
+
+      // payloads signed under the previous signer set can't be received anymore
+      const { receiverNonce } = await getStateSnapshot(tokenMint1.publicKey, user.publicKey);
+      await expect(
+        commonReceive(user, tokenMint1.publicKey, 5, receiverNonce, false, false, new Uint8Array(32), receiveExpiry(), signerEpoch)
+      ).to.be.rejectedWith("Signature invalid");
+    });
+
+    it("migrate token", async () => {
+      const mint = Keypair.generate();
+      const successorProgram = Keypair.generate().publicKey;
+      const [bridgeToken] = getBridgeTokenAccounts(mint.publicKey, program.programId);
+      await createMint(connection, admin, bridgeToken, bridgeToken, 6, mint);
+      await initializeToken(program, admin, mint.publicKey, ambTokenAddress2_, 18, true);
+
+      // only synthetic tokens can be migrated
+      await expect(
+        executeOperation(program, await queueOperation(program, admin, { migrateToken: { mint: tokenMint1.publicKey, successorProgram } }))
+      ).to.be.rejectedWith("Invalid token");
+
+      const txSignature = await executeOperation(program, await queueOperation(program, admin, { migrateToken: { mint: mint.publicKey, successorProgram } }));
+      const [newAuthority] = PublicKey.findProgramAddressSync([Buffer.from("token"), mint.publicKey.toBuffer()], successorProgram);
+      const mintInfo = await getMint(connection, mint.publicKey);
+      expect(mintInfo.mintAuthority.equals(newAuthority)).to.eq(true);
+      expect(mintInfo.freezeAuthority.equals(newAuthority)).to.eq(true);
+
+      const tokenConfig = await program.account.tokenConfig.fetch(bridgeToken);
+      expect(tokenConfig.paused).to.eq(true);
+      expect(tokenConfig.migratedTo.equals(successorProgram)).to.eq(true);
+
+      const events = await getEvents(txSignature);
+      const migrated = events.find((e) => e.name === "tokenMigrated");
+      expect(migrated.data.newAuthority.equals(newAuthority)).to.eq(true);
+
+      await expect(
+        bridgeProgram.methods.setTokenPause(false).accountsPartial({ authority: admin.publicKey, authorityRoles: null, bridgeToken }).signers([admin]).rpc()
+      ).to.be.rejectedWith("Token is migrated to another bridge");
+    });
+
+    it("operation can't be executed before delay and can be cancelled by guardian", async () => {
+      await bridgeProgram.methods.setGuardians([guardian.publicKey]).accountsPartial({ authority: admin.publicKey, authorityRoles: null }).signers([admin]).rpc();
+      // a delay the timelock couldn't recover from is rejected
+      await expect(
+        queueOperation(program, admin, { setTimelockDelay: { delay: new BN(31 * 24 * 60 * 60) } })
+      ).to.be.rejectedWith("Timelock delay too long");
+      await executeOperation(program, await queueOperation(program, admin, { setTimelockDelay: { delay: new BN(60 * 60) } }));
+
+      const operation = await queueOperation(program, admin, { withdrawFees: { receiver: admin.publicKey, amount: new BN(50) } });
+      await expect(executeOperation(program, operation)).to.be.rejectedWith("Timelock delay has not passed");
+
+      const cancel = (canceller: Keypair) => bridgeProgram.methods.cancelOperation()
+        .accountsPartial({ canceller: canceller.publicKey, queuedOperation: operation, proposer: admin.publicKey })
+        .signers([canceller]).rpc();
+
+      await expect(cancel(user)).to.be.rejectedWith("Not a guardian");
+      await cancel(guardian);
+      expect(await connection.getAccountInfo(operation)).to.eq(null);
+    });
+  });
+
+
   // helpers
 
   async function commonSend(
     userFrom: Keypair, tokenFrom: PublicKey, userTo: Uint8Array, tokenTo: Uint8Array,
     amountToSend: number,
-    isMintable = false, additionalInstructions = [], feeAmount = 20
+    isMintable = false, additionalInstructions = [], feeAmount = 20,
+    quoteId = nextQuoteId(), timestamp = Math.floor(Date.now() / 1000),
+    maxFee = feeAmount, deadline = Math.floor(Date.now() / 1000) + 60, receipt: PublicKey = null,
+    sendAccumulator: PublicKey = undefined
   ) {
 
 
     const value: SendPayload = {
+      sender: userFrom.publicKey.toBytes(),
+      recipient: userTo,
       tokenAddressFrom: tokenFrom.toBytes(),
       tokenAddressTo: tokenTo,
       amountToSend,
       feeAmount: feeAmount,
       chainFrom: SOLANA_CHAIN_ID,
       chainTo: AMB_CHAIN_ID,
-      timestamp: Date.now(),
+      timestamp,
+      quoteId,
       flags: new Uint8Array(32),
       flagData: new Uint8Array(0),
     };
@@ -664,10 +1705,12 @@ describe("my-project", () => {
 
     const verifyInstruction = verifySignatureInstruction(signature);
     // send tokens
-    const sendInstruction = await bridgeProgram.methods.send(payload, [...userTo]).accountsPartial({
+    const sendInstruction = await bridgeProgram.methods.send(payload, new BN(quoteId), new BN(maxFee), new BN(deadline)).accountsPartial({
       sender: userFrom.publicKey,
       mint: tokenFrom,
       bridgeTokenAccount: isMintable ? null : undefined,  // pass null to not use bridge token account
+      receipt,
+      sendAccumulator,
     }).signers([userFrom]).instruction();
 
     const tx = new Transaction().add(...additionalInstructions, verifyInstruction, sendInstruction);
@@ -681,7 +1724,8 @@ describe("my-project", () => {
 
   async function commonReceive(
     userTo: Keypair, token: PublicKey, amountToReceive: number,
-    receiveNonce: number, isMintable = false, shouldUnwrap = false
+    receiveNonce: number, isMintable = false, shouldUnwrap = false, flags = new Uint8Array(32),
+    expiresAt = receiveExpiry(), signerEpoch?: number
   ) {
 
     const value: ReceivePayload = {
@@ -691,7 +1735,10 @@ describe("my-project", () => {
       chainTo: SOLANA_CHAIN_ID,
       chainFrom: AMB_CHAIN_ID,
       eventId: 1,
-      flags: new Uint8Array(32),
+      expiresAt,
+      signerEpoch: signerEpoch ?? await getSignerEpoch(),
+      source: noSource(),
+      flags,
       flagData: numberToUint8Array(receiveNonce, 8)
     };
 
@@ -704,6 +1751,8 @@ describe("my-project", () => {
     const receiveInstruction = await bridgeProgram.methods.receive(
       new BN(value.amountTo),
       new BN(value.eventId),
+      new BN(value.expiresAt),
+      sourceArg(value.source),
       [...value.flags],
       Buffer.from(value.flagData)
     ).accountsPartial({
@@ -752,11 +1801,43 @@ describe("my-project", () => {
 
   }
 
+  const getQuoteRent = () => connection.getMinimumBalanceForRentExemption(8 + 32 + 8 + 1);
+
+  let lastQuoteId = 0;
+  function nextQuoteId() {
+    return ++lastQuoteId;
+  }
+
+  function noSource(): SourceRef {
+    return { receiptHash: new Uint8Array(32), txHash: new Uint8Array(32) };
+  }
+
+  function sourceArg(source: SourceRef) {
+    return { receiptHash: [...source.receiptHash], txHash: [...source.txHash] };
+  }
+
+  function receiveExpiry() {
+    return Math.floor(Date.now() / 1000) + 60 * 60;
+  }
+
+  async function getSignerEpoch() {
+    return +(await program.account.globalState.fetch(getBridgeStateAccount(program.programId))).signerEpoch;
+  }
+
   async function getEvents(txSignature: TransactionSignature) {
     const txParsed = await connection.getParsedTransaction(txSignature, { commitment: 'confirmed' });
     const eventParser = new EventParser(program.programId, new BorshCoder(program.idl));
-    const events = eventParser.parseLogs(txParsed.meta.logMessages);
-    return [...events];
+    const events = [...eventParser.parseLogs(txParsed.meta.logMessages)];
+    // built with the `event-cpi` feature, events are self-CPIs instead of logs
+    for (const { instructions } of txParsed.meta.innerInstructions ?? []) {
+      for (const ix of instructions) {
+        if (!('data' in ix) || !ix.programId.equals(program.programId)) continue;
+        const data = Buffer.from(utils.bytes.bs58.decode(ix.data));
+        if (!data.subarray(0, 8).equals(EVENT_IX_TAG_LE)) continue;
+        events.push(program.coder.events.decode(data.subarray(8).toString('base64')));
+      }
+    }
+    return events;
   }
 
 
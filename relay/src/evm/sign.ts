@@ -1,11 +1,9 @@
-import { encodePacked, keccak256 } from "viem";
-import { type ReceiptWithMeta } from "../typeValidators";
+import { encodePacked, hashMessage, keccak256 } from "viem";
+import { type MiniReceipt, type ReceiptWithMeta } from "../typeValidators";
 import { config } from "../config";
 
-export async function signReceiptForEVM(
-  receiptWithMeta: ReceiptWithMeta
-): Promise<`0x${string}` | undefined> {
-  const receipt = receiptWithMeta.receipts;
+// keccak of the packed mini receipt, the message signed for EVM bridges
+export function receiptMessageHash(receipt: MiniReceipt) {
   const message = encodePacked(
     [
       "bytes32",
@@ -28,8 +26,18 @@ export async function signReceiptForEVM(
       receipt.data === "" ? "0x" : receipt.data as `0x${string}`,
     ]
   );
+  return keccak256(message);
+}
 
-  const messageHash = keccak256(message);
+// `ReceiptUtils.toHash` of the receipt: eth-signed message hash of `receiptMessageHash`
+export function receiptHash(receipt: MiniReceipt) {
+  return hashMessage({ raw: receiptMessageHash(receipt) });
+}
+
+export async function signReceiptForEVM(
+  receiptWithMeta: ReceiptWithMeta
+): Promise<`0x${string}` | undefined> {
+  const messageHash = receiptMessageHash(receiptWithMeta.receipts);
   const signature = await config.accountEVM.signMessage({
     message: { raw: messageHash },
   });
@@ -483,6 +483,10 @@ export const bridgeIdl = {
       name: "SendEvent",
       discriminator: [140, 14, 81, 77, 70, 218, 3, 30],
     },
+    {
+      name: "SendEventV2",
+      discriminator: [96, 5, 227, 108, 87, 70, 188, 169],
+    },
   ],
   errors: [
     {
@@ -517,6 +521,10 @@ export const bridgeIdl = {
       type: {
         kind: "struct",
         fields: [
+          {
+            name: "version",
+            type: "u8",
+          },
           {
             name: "admin",
             type: "pubkey",
@@ -534,8 +542,46 @@ export const bridgeIdl = {
             type: "u64",
           },
           {
-            name: "pause",
-            type: "bool",
+            name: "pause_flags",
+            type: "u8",
+          },
+          {
+            name: "pause_reason",
+            type: "u16",
+          },
+          {
+            name: "guardians",
+            type: {
+              vec: "pubkey",
+            },
+          },
+          {
+            name: "timelock_delay",
+            type: "u64",
+          },
+          {
+            name: "timelock_nonce",
+            type: "u64",
+          },
+          {
+            name: "native_send_amount",
+            type: "u64",
+          },
+          {
+            name: "signer_epoch",
+            type: "u64",
+          },
+          {
+            name: "send_root",
+            type: {
+              array: ["u8", 32],
+            },
+          },
+          {
+            name: "reserved",
+            type: {
+              array: ["u8", 16],
+            },
           },
         ],
       },
@@ -646,6 +692,86 @@ export const bridgeIdl = {
         ],
       },
     },
+    {
+      name: "SendEventV2",
+      type: {
+        kind: "struct",
+        fields: [
+          {
+            name: "schema_version",
+            type: "u8",
+          },
+          {
+            name: "from",
+            type: "pubkey",
+          },
+          {
+            name: "to",
+            type: {
+              array: ["u8", 20],
+            },
+          },
+          {
+            name: "token_address_from",
+            type: "pubkey",
+          },
+          {
+            name: "token_address_to",
+            type: {
+              array: ["u8", 20],
+            },
+          },
+          {
+            name: "amount_from",
+            type: "u64",
+          },
+          {
+            name: "amount_to",
+            type: {
+              array: ["u8", 32],
+            },
+          },
+          {
+            name: "chain_from",
+            type: "u64",
+          },
+          {
+            name: "chain_to",
+            type: "u64",
+          },
+          {
+            name: "event_id",
+            type: "u64",
+          },
+          {
+            name: "flags",
+            type: {
+              array: ["u8", 32],
+            },
+          },
+          {
+            name: "flag_data",
+            type: "bytes",
+          },
+          {
+            name: "fee_amount",
+            type: "u64",
+          },
+          {
+            name: "fee_token",
+            type: "pubkey",
+          },
+          {
+            name: "quote_timestamp",
+            type: "u64",
+          },
+          {
+            name: "slot",
+            type: "u64",
+          },
+        ],
+      },
+    },
     {
       name: "TokenConfig",
       type: {
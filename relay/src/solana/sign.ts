@@ -1,14 +1,26 @@
 import nacl from "tweetnacl";
-import { accountSolana } from "../config";
+import { Connection } from "@solana/web3.js";
+import { accountSolana, config } from "../config";
 import { ReceiptWithMeta } from "../typeValidators";
-import { ReceivePayload, serializeReceivePayload } from "./utils";
-import { toBytes, bytesToHex } from "viem"
+import { receiptHash } from "../evm/sign";
+import { getSignerEpoch } from "./state";
+import { validateExistingTransactionSolana } from "./txValidator";
+import { ReceivePayload, RefundPayload, serializeReceivePayload, serializeRefundPayload } from "./utils";
+import { toBytes, bytesToHex, createPublicClient, http } from "viem"
 import { Hash } from "ox";
 
+// how long a signed receive payload can be claimed, in seconds.
+// Counted from the receipt timestamp, so every validator signs the same payload.
+const RECEIVE_PAYLOAD_VALIDITY = 7n * 24n * 60n * 60n;
+
 export async function signReceiptForSolana(
   receiptWithMeta: ReceiptWithMeta
 ): Promise<`0x${string}` | undefined> {
   const receipt = receiptWithMeta.receipts;
+  const txHash = receiptWithMeta.receiptsMeta?.transactionHash;
+  // receipts to Solana come from AMB, so the transaction hash is a 0x-prefixed 32-byte hash
+  if (!txHash?.startsWith("0x")) return undefined;
+  const connection = config.rpcConfig[`RPC_URL_${receipt.chainTo}`] as Connection;
   const payload: ReceivePayload = {
     to: toBytes(receipt.to, { size: 32 }),
     tokenAddressTo: toBytes(receipt.tokenAddressTo, { size: 32 }),
@@ -16,6 +28,10 @@ export async function signReceiptForSolana(
     chainTo: receipt.chainTo,
     chainFrom: receipt.chainFrom,
     eventId: receipt.eventId,
+    expiresAt: receipt.timestamp + RECEIVE_PAYLOAD_VALIDITY,
+    signerEpoch: await getSignerEpoch(connection),
+    receiptHash: toBytes(receiptHash(receipt)),
+    txHash: toBytes(txHash as `0x${string}`, { size: 32 }),
     flags: toBytes(receipt.flags, { size: 32 }),
     flagData: toBytes(receipt.data),
   };
@@ -27,3 +43,48 @@ export async function signReceiptForSolana(
   );
   return bytesToHex(signatureBytes); // signature;
 }
+
+
+const isClaimedAbi = [
+  {
+    name: "isClaimed",
+    type: "function",
+    stateMutability: "view",
+    inputs: [{ name: "hash", type: "bytes32" }],
+    outputs: [{ name: "claimed", type: "bool" }],
+  },
+] as const;
+
+// Refund attestation for a Solana send that AMB will never accept.
+// A send is either claimed on AMB or refunded, never both: a send claimed on AMB is not refunded here,
+// and `validateExistingTransactionSolana` doesn't let the AMB claim be signed once the send is refunded.
+export async function signRefundForSolana(
+  receiptWithMeta: ReceiptWithMeta,
+  ambBridgeAddress: `0x${string}`
+): Promise<`0x${string}`> {
+  const receipt = receiptWithMeta.receipts;
+  await validateExistingTransactionSolana(receiptWithMeta);
+  const rpcTo = config.rpcConfig[`RPC_URL_${receipt.chainTo}`] as string;
+  if (!rpcTo) {
+    throw new Error(`RPC URL for chain ${receipt.chainTo} not found.`);
+  }
+  const claimed = await createPublicClient({ transport: http(rpcTo) }).readContract({
+    address: ambBridgeAddress,
+    abi: isClaimedAbi,
+    functionName: "isClaimed",
+    args: [receiptHash(receipt)],
+  });
+  if (claimed) {
+    throw new Error(`Send ${receipt.eventId} is already claimed on AMB.`);
+  }
+  const payload: RefundPayload = {
+    eventId: receipt.eventId,
+    sender: toBytes(receipt.from, { size: 32 }),
+    tokenAddress: toBytes(receipt.tokenAddressFrom, { size: 32 }),
+    amount: receipt.amountFrom,
+    chainFrom: receipt.chainFrom,
+    chainTo: receipt.chainTo,
+  };
+  const messageHashBytes = Hash.keccak256(serializeRefundPayload(payload));
+  return bytesToHex(nacl.sign.detached(messageHashBytes, accountSolana.secretKey));
+}
@@ -0,0 +1,26 @@
+import { test } from "node:test";
+import assert from "node:assert/strict";
+import { Buffer } from "buffer";
+import { PublicKey } from "@solana/web3.js";
+import { bridgeIdl } from "./idl/bridgeIdl";
+import { getRefundedSendPda, isSendRefunded } from "./state";
+
+const bridgeProgramId = new PublicKey(bridgeIdl.address);
+
+test("refunded send PDA uses the bridge seeds", () => {
+  const [expected] = PublicKey.findProgramAddressSync(
+    [Buffer.from("refund"), Buffer.from([7, 1, 0, 0, 0, 0, 0, 0])],
+    bridgeProgramId
+  );
+  assert.ok(getRefundedSendPda(263n).equals(expected));
+});
+
+test("a send is refunded once its marker exists", async () => {
+  const refunded = getRefundedSendPda(5n);
+  const connection = {
+    getAccountInfo: async (address: PublicKey) =>
+      address.equals(refunded) ? ({} as any) : null,
+  };
+  assert.equal(await isSendRefunded(connection, 5n), true);
+  assert.equal(await isSendRefunded(connection, 6n), false);
+});
@@ -0,0 +1,40 @@
+import { Connection, PublicKey } from "@solana/web3.js";
+import { Buffer } from "buffer";
+import { BorshCoder } from "@coral-xyz/anchor";
+import { bridgeIdl } from "./idl/bridgeIdl";
+
+const bridgeProgramId = new PublicKey(bridgeIdl.address);
+const [globalStatePda] = PublicKey.findProgramAddressSync(
+  [Buffer.from("global_state")],
+  bridgeProgramId
+);
+
+// `GlobalState.signer_epoch`; receive payloads signed under another epoch are rejected by the bridge
+export async function getSignerEpoch(connection: Connection): Promise<bigint> {
+  const account = await connection.getAccountInfo(globalStatePda, "confirmed");
+  if (!account) {
+    throw new Error(`Bridge state ${globalStatePda.toBase58()} not found.`);
+  }
+  const state = new BorshCoder(bridgeIdl).accounts.decode("GlobalState", account.data);
+  return BigInt(state.signer_epoch.toString());
+}
+
+// `RefundedSend` marker the bridge creates when the send with `eventId` is refunded
+export function getRefundedSendPda(eventId: bigint): PublicKey {
+  const seed = Buffer.alloc(8);
+  seed.writeBigUInt64LE(eventId);
+  const [refundedSend] = PublicKey.findProgramAddressSync(
+    [Buffer.from("refund"), seed],
+    bridgeProgramId
+  );
+  return refundedSend;
+}
+
+// a refunded send must never be claimed on AMB as well
+export async function isSendRefunded(
+  connection: Pick<Connection, "getAccountInfo">,
+  eventId: bigint
+): Promise<boolean> {
+  const account = await connection.getAccountInfo(getRefundedSendPda(eventId), "confirmed");
+  return account !== null;
+}
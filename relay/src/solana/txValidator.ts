@@ -1,9 +1,29 @@
-import { Connection, PublicKey } from "@solana/web3.js";
+import { Connection, ParsedTransactionWithMeta, PublicKey } from "@solana/web3.js";
+import { Buffer } from "buffer";
 import { config } from "../config";
 import { type ReceiptMeta, type ReceiptWithMeta } from "../typeValidators";
 import { bridgeIdl } from "./idl/bridgeIdl";
-import { BorshCoder, EventParser, Program } from "@coral-xyz/anchor";
+import { BorshCoder, EventParser, Program, utils as anchorUtils } from "@coral-xyz/anchor";
 import * as utils from "./utils";
+import { isSendRefunded } from "./state";
+
+// Anchor's tag of self-CPI event instructions (`EVENT_IX_TAG_LE`), followed by the event discriminator and data
+const EVENT_IX_TAG_LE = Buffer.from([0xe4, 0x45, 0xa5, 0x2e, 0x51, 0xcb, 0x9a, 0x1d]);
+
+// events of a bridge built with the `event-cpi` feature, emitted as self-CPIs instead of logs
+function parseCpiEvents(
+  transaction: ParsedTransactionWithMeta,
+  programId: PublicKey,
+  coder: BorshCoder
+) {
+  return (transaction.meta?.innerInstructions ?? [])
+    .flatMap(({ instructions }) => instructions)
+    .filter((ix) => "data" in ix && ix.programId.equals(programId))
+    .map((ix) => Buffer.from(anchorUtils.bytes.bs58.decode((ix as { data: string }).data)))
+    .filter((data) => data.subarray(0, 8).equals(EVENT_IX_TAG_LE))
+    .map((data) => coder.events.decode(data.subarray(8).toString("base64")))
+    .filter((event) => event !== null);
+}
 
 export async function validateExistingTransactionSolana(
   receiptWithMeta: ReceiptWithMeta
@@ -42,13 +62,15 @@ export async function validateExistingTransactionSolana(
     );
   }
   const program = new Program(bridgeIdl, { connection });
-  const eventParser = new EventParser(
-    program.programId,
-    new BorshCoder(program.idl)
-  );
-  const events = eventParser.parseLogs(receipt.meta.logMessages);
+  const coder = new BorshCoder(program.idl);
+  const eventParser = new EventParser(program.programId, coder);
+  const events = [
+    ...eventParser.parseLogs(receipt.meta.logMessages),
+    ...parseCpiEvents(receipt, program.programId, coder),
+  ];
   for (const event of events) {
-    if (event.name === "SendEvent") {
+    // `SendEventV2` has the same leading fields as `SendEvent`, plus the quote's terms
+    if (event.name === "SendEvent" || event.name === "SendEventV2") {
       const eventData = event.data;
       if (!eventData) {
         throw new Error(
@@ -75,6 +97,11 @@ export async function validateExistingTransactionSolana(
         throw new Error(
           `Event data does not match receipt for transaction hash ${receiptMeta.transactionHash}.`
         );
+      if (await isSendRefunded(connection, BigInt(eventData.event_id))) {
+        throw new Error(
+          `Send ${eventData.event_id} of transaction hash ${receiptMeta.transactionHash} is refunded on Solana.`
+        );
+      }
       console.log(`Transaction ${receiptMeta.transactionHash} is valid.`);
       return;
     }
@@ -70,6 +70,10 @@ export interface ReceivePayload {
   chainFrom: number | bigint;
   chainTo: number | bigint;
   eventId: number | bigint;
+  expiresAt: number | bigint;
+  signerEpoch: number | bigint;
+  receiptHash: Uint8Array;  // `ReceiptUtils.toHash` of the AMB receipt
+  txHash: Uint8Array;  // AMB transaction that emitted the receipt
   flags: Uint8Array;
   flagData: Uint8Array;
 }
@@ -81,11 +85,42 @@ const receiveSchema = {
   chainFrom: "u64",
   chainTo: "u64",
   eventId: "u64",
+  expiresAt: "u64",
+  signerEpoch: "u64",
+  receiptHash: _b32,  // `SourceRef` of the program
+  txHash: _b32,
   flags: _b32,
   flagData: { array: { type: "u8" } },
 };
 
+export interface RefundPayload {
+  eventId: number | bigint;
+  sender: Uint8Array;
+  tokenAddress: Uint8Array;
+  amount: number | bigint;
+  chainFrom: number | bigint;
+  chainTo: number | bigint;
+}
+
+const refundSchema = {
+  eventId: "u64",
+  sender: _b32,
+  tokenAddress: _b32,
+  amount: "u64",
+  chainFrom: "u64",
+  chainTo: "u64",
+};
+
 export const serializeSendPayload = (value: SendPayload) =>
   serialize(value, sendSchema);
+// `ReceiveSigned::TAG` of `ReceivePayload` in the bridge, prefixed to the signed payload
+const RECEIVE_PAYLOAD_TAG = Buffer.from("amb:recv");
+
 export const serializeReceivePayload = (value: ReceivePayload) =>
-  serialize(value, receiveSchema);
+  Buffer.concat([RECEIVE_PAYLOAD_TAG, serialize(value, receiveSchema)]);
+
+// `ReceiveSigned::TAG` of `RefundPayload` in the bridge
+const REFUND_PAYLOAD_TAG = Buffer.from("amb:rfnd");
+
+export const serializeRefundPayload = (value: RefundPayload) =>
+  Buffer.concat([REFUND_PAYLOAD_TAG, serialize(value, refundSchema)]);